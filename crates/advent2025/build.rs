@@ -0,0 +1,5 @@
+// setup_problems! discovers day modules by listing src/, so rebuild whenever a
+// file is added or removed there and not only when a known module changes.
+fn main() {
+    println!("cargo:rerun-if-changed=src");
+}
@@ -0,0 +1,186 @@
+use std::{collections::HashMap, fs::read_to_string, io::ErrorKind, path::Path};
+
+use adventlib::Answer;
+
+// Expected answers for a single day, read from data/<year>/<day>/answers.toml.
+// Only the small slice of TOML we need is understood: one table per input file
+// and one string (or integer) per part name, e.g.
+//
+//   ["example.txt"]
+//   "Example 1" = "3"
+//   "Example 2" = 6
+//
+// Triple quoted strings may be used for answers spanning multiple lines.
+#[derive(Default)]
+pub struct Answers {
+    expected: HashMap<(String, String), String>,
+}
+
+pub enum Verdict {
+    Pass,
+    Fail(String),
+    Unknown,
+}
+
+impl Answers {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match read_to_string(path) {
+            Ok(content) => {
+                Self::parse(&content).map_err(|err| format!("{}: {}", path.display(), err))
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Answers::default()),
+            Err(err) => Err(format!("{}: {}", path.display(), err)),
+        }
+    }
+
+    fn parse(s: &str) -> Result<Self, String> {
+        let mut answers = Answers::default();
+        let mut file: Option<String> = None;
+        let mut lines = s.lines().enumerate();
+        while let Some((i, raw)) = lines.next() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                file = Some(unquote(line[1..(line.len() - 1)].trim()));
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {}: expected key = value", i + 1))?;
+            let file = file
+                .clone()
+                .ok_or_else(|| format!("line {}: answer outside of a [file] table", i + 1))?;
+            let value = value.trim();
+            let answer = if let Some(rest) = value.strip_prefix("\"\"\"") {
+                let mut body = vec![];
+                let mut rest = rest.trim_start_matches('\n').to_string();
+                loop {
+                    if let Some(end) = rest.find("\"\"\"") {
+                        body.push(rest[..end].to_string());
+                        break;
+                    }
+                    body.push(rest);
+                    rest = lines
+                        .next()
+                        .map(|(_, l)| l.to_string())
+                        .ok_or_else(|| format!("line {}: unterminated string", i + 1))?;
+                }
+                body.join("\n").trim_matches('\n').to_string()
+            } else {
+                unquote(value)
+            };
+            answers.expected.insert((file, unquote(key.trim())), answer);
+        }
+        Ok(answers)
+    }
+
+    pub fn check(&self, file: &str, name: &str, actual: Option<&Answer>) -> Verdict {
+        match (
+            self.expected.get(&(file.to_string(), name.to_string())),
+            actual,
+        ) {
+            (None, _) => Verdict::Unknown,
+            (Some(expected), Some(actual)) if actual.matches(expected) => Verdict::Pass,
+            (Some(expected), actual) => Verdict::Fail(diff(
+                expected,
+                &actual.map_or(String::new(), |a| a.to_string()),
+            )),
+        }
+    }
+}
+
+// Decodes a basic TOML string in a single pass so each escape is read once,
+// leaving unknown escapes as they are
+fn unquote(s: &str) -> String {
+    if s.len() < 2 || !s.starts_with('"') || !s.ends_with('"') {
+        return s.to_string();
+    }
+    let mut out = String::new();
+    let mut chars = s[1..(s.len() - 1)].chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some(c @ ('"' | '\\')) => out.push(c),
+            Some(c) => out.extend(['\\', c]),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+// Line based diff which is plenty for puzzle answers: single line answers show
+// both values and grids only show the rows which differ.
+fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<_> = expected.lines().collect();
+    let actual: Vec<_> = actual.lines().collect();
+    let mut out = vec![];
+    for i in 0..expected.len().max(actual.len()) {
+        let (e, a) = (expected.get(i), actual.get(i));
+        if e != a {
+            if let Some(e) = e {
+                out.push(format!("     - {}", e));
+            }
+            if let Some(a) = a {
+                out.push(format!("     + {}", a));
+            }
+        }
+    }
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(s: &str) -> String {
+        let escaped = (s.replace('\\', "\\\\"))
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+            .replace('\t', "\\t");
+        format!("\"{}\"", escaped)
+    }
+
+    #[test]
+    fn escapes_are_decoded_once() {
+        assert_eq!(unquote(r#""a\\nb""#), r"a\nb");
+        assert_eq!(unquote(r#""a\nb""#), "a\nb");
+        assert_eq!(unquote(r#""say \"hi\"\t\\""#), "say \"hi\"\t\\");
+        assert_eq!(unquote(r#""\x""#), r"\x");
+        assert_eq!(unquote("plain"), "plain");
+    }
+
+    #[test]
+    fn quoted_answers_round_trip() {
+        let answers = [
+            "3",
+            r"a\nb",
+            "a\nb",
+            r"\\",
+            "\"quoted\"",
+            "tab\there",
+            r"\",
+            "",
+        ];
+        for answer in answers {
+            assert_eq!(unquote(&quote(answer)), answer, "{}", quote(answer));
+            let toml = format!(
+                "[{}]\n{} = {}\n",
+                quote("input.txt"),
+                quote("Part 1"),
+                quote(answer)
+            );
+            let parsed = Answers::parse(&toml).unwrap();
+            let expected = parsed
+                .expected
+                .get(&("input.txt".to_string(), "Part 1".to_string()));
+            assert_eq!(expected.map(String::as_str), Some(answer), "{}", toml);
+        }
+    }
+}
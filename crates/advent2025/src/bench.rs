@@ -0,0 +1,98 @@
+use std::{
+    collections::HashMap,
+    fs::{read_to_string, write},
+    path::Path,
+};
+
+// Summary of repeated timings of a single part, all in milliseconds
+#[derive(Clone, Copy)]
+pub struct Stats {
+    pub runs: usize,
+    pub min: f64,
+    pub median: f64,
+    pub mean: f64,
+    pub stddev: f64,
+}
+
+impl Stats {
+    pub fn from_samples(mut samples: Vec<f64>) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort_by(|a, b| a.total_cmp(b));
+        let n = samples.len();
+        let median = if n.is_multiple_of(2) {
+            (samples[n / 2 - 1] + samples[n / 2]) / 2.0
+        } else {
+            samples[n / 2]
+        };
+        let mean = samples.iter().sum::<f64>() / n as f64;
+        let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / n as f64;
+        Some(Stats {
+            runs: n,
+            min: samples[0],
+            median: median,
+            mean: mean,
+            stddev: variance.sqrt(),
+        })
+    }
+}
+
+// Median timings from a previous benchmark run keyed by year, day, part label and
+// input file. Stored as simple tab separated lines so it diffs nicely.
+#[derive(Default)]
+pub struct Baseline {
+    medians: HashMap<(u32, usize, String, String), f64>,
+}
+
+impl Baseline {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        let mut baseline = Baseline::default();
+        for (i, line) in content.lines().enumerate().filter(|(_, l)| !l.is_empty()) {
+            let fields: Vec<_> = line.split('\t').collect();
+            let bad_line = || format!("{}:{}: malformed baseline entry", path.display(), i + 1);
+            if fields.len() != 5 {
+                return Err(bad_line());
+            }
+            let year = fields[0].parse().map_err(|_| bad_line())?;
+            let day = fields[1].parse().map_err(|_| bad_line())?;
+            let median = fields[4].parse().map_err(|_| bad_line())?;
+            let key = (year, day, fields[2].to_string(), fields[3].to_string());
+            baseline.medians.insert(key, median);
+        }
+        Ok(baseline)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let mut entries: Vec<_> = self.medians.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        let content: String = entries
+            .iter()
+            .map(|((year, day, label, file), median)| {
+                format!("{year}\t{day}\t{label}\t{file}\t{median}\n")
+            })
+            .collect();
+        write(path, content).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    pub fn insert(&mut self, year: u32, day: usize, label: &str, file: &str, median: f64) {
+        (self.medians).insert((year, day, label.to_string(), file.to_string()), median);
+    }
+
+    // Percentage change of the given median relative to the baseline, positive
+    // values being regressions.
+    pub fn change(
+        &self,
+        year: u32,
+        day: usize,
+        label: &str,
+        file: &str,
+        median: f64,
+    ) -> Option<f64> {
+        self.medians
+            .get(&(year, day, label.to_string(), file.to_string()))
+            .filter(|base| **base > 0.0)
+            .map(|base| (median - base) / base * 100.0)
+    }
+}
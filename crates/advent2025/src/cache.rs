@@ -0,0 +1,72 @@
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::{Arc, Mutex, OnceLock},
+    time::Instant,
+};
+
+use adventlib::stages::{Parsed, ParserId, Stages};
+
+use crate::{
+    mem::{self, MemStats},
+    ms_since,
+};
+
+// Identifies an input by year, day and the label of its file
+pub type InputKey = (u32, usize, String);
+
+// Values computed at most once per key, each behind its own lock
+pub type Slots<K, V> = Mutex<HashMap<K, Arc<OnceLock<V>>>>;
+
+pub fn slot<K: Eq + Hash, V>(slots: &Slots<K, V>, key: K) -> Arc<OnceLock<V>> {
+    slots.lock().unwrap().entry(key).or_default().clone()
+}
+
+pub struct Parse {
+    pub parsed: Result<Arc<Parsed>, String>,
+    pub elapsed_ms: f64,
+    pub mem: Option<MemStats>,
+}
+
+// File contents and parsed values shared by every part reading the same input.
+// Each is only produced once, even when parts run in parallel, as later
+// arrivals wait on the first one's slot rather than the whole cache.
+#[derive(Default)]
+pub struct Cache {
+    contents: Slots<InputKey, Result<Arc<String>, String>>,
+    parsed: Slots<(InputKey, ParserId), Arc<Parse>>,
+}
+
+impl Cache {
+    pub fn content(
+        &self,
+        key: &InputKey,
+        load: impl FnOnce() -> Result<String, String>,
+    ) -> Result<Arc<String>, String> {
+        let slot = slot(&self.contents, key.clone());
+        slot.get_or_init(|| load().map(Arc::new)).clone()
+    }
+
+    // Parses the input with the given stages unless a part sharing its parser
+    // already has, also returning whether this call did the parsing
+    pub fn parse(&self, key: &InputKey, stages: &dyn Stages, input: &str) -> (Arc<Parse>, bool) {
+        let slot = slot(&self.parsed, (key.clone(), stages.parser()));
+        let mut parsed_here = false;
+        let parse = slot.get_or_init(|| {
+            parsed_here = true;
+            let ((parsed, elapsed), mem) = mem::measure(|| {
+                let start = Instant::now();
+                let parsed = stages.parse(input);
+                (parsed, ms_since(start))
+            });
+            Arc::new(Parse {
+                parsed: parsed
+                    .map(Arc::new)
+                    .map_err(|err| err.locate(input).to_string()),
+                elapsed_ms: elapsed,
+                mem: mem,
+            })
+        });
+        (parse.clone(), parsed_here)
+    }
+}
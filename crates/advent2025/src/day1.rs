@@ -1,44 +1,64 @@
 use std::vec::Vec;
 
 use adventlib::{
-    Part, all_parts,
+    Solution,
+    error::SolveError,
     util::{parse, posmod},
 };
+use adventproc::solver;
 
 const START: i32 = 50;
 const SIZE: i32 = 100;
 
-pub static PARTS: &'static [Part<'static>] = &all_parts![count_zeros, count_zero_passes];
-
-fn parse_rotation(rot: &str) -> i32 {
-    parse::<i32>(&rot[1..]) * ((rot.starts_with('R') as i32) * 2 - 1)
+fn parse_rotation(rot: &str) -> Result<i32, SolveError> {
+    let sign = match rot.chars().next() {
+        Some('R') => 1,
+        Some('L') => -1,
+        _ => return Err(SolveError::parse(rot, format!("Bad rotation: [{}]", rot))),
+    };
+    Ok(parse::<i32>(&rot[1..])? * sign)
 }
 
-fn get_rotations(input: &str) -> impl Iterator<Item = i32> {
+fn get_rotations(input: &str) -> impl Iterator<Item = Result<i32, SolveError>> {
     input.split('\n').map(parse_rotation)
 }
 
-fn get_dial_positions(mut pos: i32, input: &str) -> Vec<(i32, i32)> {
+fn get_dial_positions(mut pos: i32, input: &str) -> Result<Vec<(i32, i32)>, SolveError> {
     let mut rotations = Vec::new();
     for rot in get_rotations(input) {
+        let rot = rot?;
         pos = posmod(pos + rot, SIZE);
         rotations.push((rot, pos));
     }
-    return rotations;
+    return Ok(rotations);
 }
 
-pub fn count_zeros(input: &str) -> String {
-    get_dial_positions(START, input)
+#[solver(
+    day = 1,
+    part = 1,
+    input = "input.txt",
+    example = "example.txt",
+    expected = 3
+)]
+pub fn count_zeros(input: &str) -> Solution {
+    Ok(get_dial_positions(START, input)?
         .iter()
         .filter(|(_, pos)| *pos == 0)
         .count()
-        .to_string()
+        .into())
 }
 
-pub fn count_zero_passes(input: &str) -> String {
+#[solver(
+    day = 1,
+    part = 2,
+    input = "input.txt",
+    example = "example.txt",
+    expected = 6
+)]
+pub fn count_zero_passes(input: &str) -> Solution {
     let mut last = START;
     let mut count = 0;
-    for (change, pos) in get_dial_positions(last, input) {
+    for (change, pos) in get_dial_positions(last, input)? {
         // Count how many full rotations the dial did and then a bit extra if
         // the overall delta is "pointing" differently than the rotation.
         count += change.abs() / SIZE;
@@ -48,5 +68,5 @@ pub fn count_zero_passes(input: &str) -> String {
         }
         last = pos;
     }
-    return count.to_string();
+    return Ok(count.into());
 }
@@ -1,7 +1,9 @@
 use std::collections::{HashSet, VecDeque};
 
 use adventlib::{
-    Part, all_parts,
+    Part, Solution, all_parts,
+    cancel::Cancel,
+    error::SolveError,
     fraction::Fraction,
     ilp::{Bound, LinearEquation, LinearSystem, ReducedRowEcheleon},
     util::{mid, parse},
@@ -9,8 +11,8 @@ use adventlib::{
 
 #[rustfmt::skip]
 pub static PARTS: &'static [Part<'static>] = &all_parts![
-    |i| sum_fewest_presses(i, fewest_light_presses),
-    |i| sum_fewest_presses(i, fewest_joltage_presses)
+    |i| sum_fewest_presses(i, fewest_light_presses) => "7",
+    |i| sum_fewest_presses(i, fewest_joltage_presses) => "33"
 ];
 
 struct Button {
@@ -19,11 +21,16 @@ struct Button {
 }
 
 impl Button {
-    fn parse(index: usize, s: &str) -> Self {
-        Button {
+    fn parse(index: usize, s: &str) -> Result<Self, SolveError> {
+        let wires = (mid(s, 1))
+            .ok_or_else(|| SolveError::parse(s, "Expected '(wires)'"))?
+            .split(',')
+            .map(parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Button {
             index: 1 << index,
-            wires: pack_bit_indexes(mid(s, 1).split(',').map(parse)),
-        }
+            wires: pack_bit_indexes(wires.into_iter()),
+        })
     }
 }
 
@@ -33,12 +40,21 @@ struct Machine {
     joltage: Vec<usize>,
 }
 
-impl From<&str> for Machine {
-    fn from(s: &str) -> Self {
+impl TryFrom<&str> for Machine {
+    type Error = SolveError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
         let parts: Vec<_> = s.split_ascii_whitespace().collect();
-        Machine {
+        if parts.len() < 2 {
+            return Err(SolveError::parse(
+                s,
+                "Expected lights, buttons and joltages",
+            ));
+        }
+        Ok(Machine {
             lights: pack_bit_indexes(
-                mid(parts[0], 1)
+                (mid(parts[0], 1))
+                    .ok_or_else(|| SolveError::parse(parts[0], "Expected '[lights]'"))?
                     .chars()
                     .enumerate()
                     .filter_map(move |(i, c)| if c == '#' { Some(i) } else { None }),
@@ -47,45 +63,49 @@ impl From<&str> for Machine {
                 .iter()
                 .enumerate()
                 .map(|(i, bs)| Button::parse(i, bs))
-                .collect(),
-            joltage: mid(parts[parts.len() - 1], 1)
+                .collect::<Result<_, _>>()?,
+            joltage: (mid(parts[parts.len() - 1], 1))
+                .ok_or_else(|| SolveError::parse(parts[parts.len() - 1], "Expected '{joltages}'"))?
                 .split(',')
                 .map(parse)
-                .collect(),
-        }
+                .collect::<Result<_, _>>()?,
+        })
     }
 }
 
-impl Into<LinearSystem> for Machine {
-    fn into(self) -> LinearSystem {
+#[allow(clippy::from_over_into)]
+impl Into<LinearSystem<i64>> for Machine {
+    fn into(self) -> LinearSystem<i64> {
         (self.joltage.iter())
             .enumerate()
             .map(|(i, j)| LinearEquation {
                 a: self
                     .buttons
                     .iter()
-                    .map(|b| (((b.wires >> i) & 1) as i32).into())
+                    .map(|b| (((b.wires >> i) & 1) as i64).into())
                     .collect(),
-                b: (*j as i32).into(),
+                b: (*j as i64).into(),
             })
             .collect()
     }
 }
 
-impl Into<ReducedRowEcheleon> for Machine {
-    fn into(self) -> ReducedRowEcheleon {
-        Into::<LinearSystem>::into(self).into()
+impl TryFrom<Machine> for ReducedRowEcheleon<i64> {
+    type Error = SolveError;
+
+    fn try_from(machine: Machine) -> Result<Self, Self::Error> {
+        Into::<LinearSystem<i64>>::into(machine).try_into()
     }
 }
 
 impl Machine {
-    fn solve(&self, target: usize) -> usize {
+    fn solve(&self, target: usize) -> Result<usize, SolveError> {
         let mut edge = VecDeque::from([(0, 0)]);
         let mut seen = HashSet::new();
 
         while let Some((state, buttons)) = edge.pop_front() {
             if state == target {
-                return buttons;
+                return Ok(buttons);
             }
             if !seen.insert(buttons) {
                 continue;
@@ -96,7 +116,9 @@ impl Machine {
                 }
             });
         }
-        panic!("No solution");
+        Err(SolveError::no_solution(
+            "No button combination reaches the lights",
+        ))
     }
 }
 
@@ -104,26 +126,33 @@ pub fn pack_bit_indexes(bits: impl Iterator<Item = usize>) -> usize {
     bits.fold(0, |a, b| a + (1 << b))
 }
 
-fn sum_fewest_presses(input: &str, solver: fn(Machine) -> usize) -> String {
-    input
+fn sum_fewest_presses(input: &str, solver: fn(Machine) -> Result<usize, SolveError>) -> Solution {
+    Ok(input
         .split('\n')
-        .map(Machine::from)
-        .map(solver)
-        .sum::<usize>()
-        .to_string()
+        .map(|line| Machine::try_from(line).and_then(solver))
+        .sum::<Result<usize, _>>()?
+        .into())
 }
 
-fn fewest_light_presses(machine: Machine) -> usize {
-    machine.solve(machine.lights).count_ones() as usize
+fn fewest_light_presses(machine: Machine) -> Result<usize, SolveError> {
+    Ok(machine.solve(machine.lights)?.count_ones() as usize)
 }
 
-fn fewest_joltage_presses(machine: Machine) -> usize {
-    let mut rre: ReducedRowEcheleon = machine.into();
-    (0..rre.get_var_count())
-        .for_each(|i| rre.restrict_bound(i, Bound::closed_low(Fraction::from(0))));
-    rre.infer_bounds();
-    rre.get_solutions()
-        .map(|r| r.iter().sum::<i32>() as usize)
-        .min()
-        .expect("no solution")
+fn fewest_joltage_presses(machine: Machine) -> Result<usize, SolveError> {
+    let mut rre: ReducedRowEcheleon<i64> = machine.try_into()?;
+    for i in 0..rre.get_var_count() {
+        rre.restrict_bound(i, Bound::closed_low(Fraction::from(0)))?;
+    }
+    rre.infer_bounds()?;
+    rre.print_info();
+    // Enumerating can take very long on loose bounds, so stop once timed out
+    let cancel = Cancel::current();
+    let fewest = (rre.get_solutions())
+        .take_while(|_| !cancel.is_cancelled())
+        .try_fold(None, |fewest: Option<usize>, r| {
+            let presses = r?.iter().sum::<i64>() as usize;
+            Ok::<_, SolveError>(Some(fewest.map_or(presses, |f| f.min(presses))))
+        })?;
+    cancel.check()?;
+    fewest.ok_or_else(|| SolveError::no_solution("No non-negative integer press counts"))
 }
@@ -1,69 +1,80 @@
 use std::collections::{HashMap, HashSet};
 
-use adventlib::Part;
+use adventlib::{Solution, bigint::BigInt, error::SolveError};
+use adventproc::{parser, solver};
 
-pub static PARTS: &'static [Part<'static>] = &[
-    Part::new("Example", "example.txt", count_all_paths),
-    Part::new("Part", "input.txt", count_all_paths),
-    Part::new("Example", "example2.txt", count_fft_dac_paths),
-    Part::new("Part", "input.txt", count_fft_dac_paths),
-];
-
-struct Dag<'a> {
-    edge_out: HashMap<&'a str, Vec<&'a str>>,
-    edge_in: HashMap<&'a str, Vec<&'a str>>,
+// Owns its node names so it can be parsed once and shared by both parts
+struct Dag {
+    edge_out: HashMap<String, Vec<String>>,
+    edge_in: HashMap<String, Vec<String>>,
 }
 
-impl<'a> Dag<'a> {
-    fn parse(s: &'a str) -> Self {
-        let edge_out: HashMap<&str, Vec<_>> = s
+impl Dag {
+    fn parse(s: &str) -> Result<Self, SolveError> {
+        let edge_out: HashMap<String, Vec<_>> = s
             .split('\n')
             .map(|line| {
-                let (k, v) = line.split_once(": ").unwrap();
-                (k, v.split_whitespace().collect())
+                let (k, v) = line
+                    .split_once(": ")
+                    .ok_or_else(|| SolveError::parse(line, "Expected 'node: outputs'"))?;
+                Ok((
+                    k.to_string(),
+                    v.split_whitespace().map(String::from).collect(),
+                ))
             })
-            .collect();
+            .collect::<Result<_, SolveError>>()?;
 
         let edge_in = edge_out
             .iter()
             .flat_map(|(k, v)| v.iter().map(move |vv| (vv, k)))
-            .fold(HashMap::<&str, Vec<&str>>::new(), |mut h, (v, k)| {
+            .fold(HashMap::<String, Vec<String>>::new(), |mut h, (v, k)| {
                 if !h.contains_key(v) {
-                    h.insert(v, vec![]);
+                    h.insert(v.clone(), vec![]);
                 }
                 let ks = h.get_mut(v).unwrap();
-                ks.push(k);
+                ks.push(k.clone());
                 h
             });
-        Dag {
+        Ok(Dag {
             edge_in: edge_in,
             edge_out: edge_out,
-        }
+        })
     }
 
-    fn get_upstream(&'a self, start: &'a str) -> HashSet<&'a str> {
+    fn get_upstream<'a>(&'a self, start: &'a str) -> HashSet<&'a str> {
         let mut result = HashSet::new();
-        let mut edge = self.edge_in.get(start).unwrap_or(&vec![]).clone();
+        let mut edge: Vec<&str> = self
+            .edge_in
+            .get(start)
+            .map_or(vec![], |e| e.iter().map(String::as_str).collect());
         while let Some(node) = edge.pop() {
             if result.contains(node) {
                 continue;
             }
             result.insert(node);
-            edge.extend(self.edge_in.get(node).unwrap_or(&vec![]));
+            edge.extend(
+                self.edge_in
+                    .get(node)
+                    .into_iter()
+                    .flatten()
+                    .map(String::as_str),
+            );
         }
         return result;
     }
 
-    fn count_kahn(&self, start: &str, end: &str, ignore: &str) -> usize {
+    // Path counts grow exponentially with the size of the graph
+    #[allow(clippy::len_zero)]
+    fn count_kahn(&self, start: &str, end: &str, ignore: &str) -> BigInt {
         let upstream = self.get_upstream(end);
         let mut nodes = upstream.clone();
         nodes.remove(ignore);
-        let mut count: HashMap<&str, usize> = HashMap::from([(end, 1)]);
+        let mut count: HashMap<&str, BigInt> = HashMap::from([(end, BigInt::from(1))]);
         while !nodes.is_empty() && !count.contains_key(start) {
             let edge: Vec<_> = nodes
                 .extract_if(|node| {
                     (self.edge_out[*node].iter())
-                        .all(|e| !upstream.contains(e) || count.contains_key(e))
+                        .all(|e| !upstream.contains(e.as_str()) || count.contains_key(e.as_str()))
                 })
                 .collect();
             if edge.len() == 0 {
@@ -71,26 +82,44 @@ impl<'a> Dag<'a> {
             }
             for node in edge {
                 let sum = (self.edge_out[node].iter())
-                    .map(|e| count.get(e).unwrap_or(&0))
-                    .sum::<usize>();
+                    .filter_map(|e| count.get(e.as_str()).cloned())
+                    .sum::<BigInt>();
                 count.insert(node, sum);
             }
         }
-        *count.get(start).unwrap_or(&0)
+        count.remove(start).unwrap_or_default()
     }
 }
 
-fn count_all_paths(input: &str) -> String {
-    Dag::parse(input).count_kahn("you", "out", "").to_string()
+#[parser]
+fn parse_dag(input: &str) -> Result<Dag, SolveError> {
+    Dag::parse(input)
+}
+
+#[solver(
+    day = 11,
+    part = 1,
+    input = "input.txt",
+    example = "example.txt",
+    expected = 5
+)]
+fn count_all_paths(dag: &Dag) -> Solution {
+    Ok(dag.count_kahn("you", "out", "").into())
 }
 
-fn count_fft_dac_paths(input: &str) -> String {
-    let dag = Dag::parse(input);
+#[solver(
+    day = 11,
+    part = 2,
+    input = "input.txt",
+    example = "example2.txt",
+    expected = 2
+)]
+fn count_fft_dac_paths(dag: &Dag) -> Solution {
     let fft_dac = dag.count_kahn("svr", "fft", "dac")
         * dag.count_kahn("fft", "dac", "")
         * dag.count_kahn("dac", "out", "fft");
     let dac_fft = dag.count_kahn("svr", "dac", "fft")
         * dag.count_kahn("dac", "fft", "")
         * dag.count_kahn("fft", "out", "dac");
-    (fft_dac + dac_fft).to_string()
+    Ok((fft_dac + dac_fft).into())
 }
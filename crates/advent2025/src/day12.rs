@@ -1,8 +1,7 @@
 use std::collections::HashSet;
 
-use adventlib::{Part, util::parse};
-
-pub static PARTS: &'static [Part<'static>] = &[Part::new("Input", "input.txt", solve)];
+use adventlib::{Solution, error::SolveError, util::parse};
+use adventproc::solver;
 
 struct Region {
     width: u32,
@@ -11,33 +10,44 @@ struct Region {
 }
 
 impl Region {
-    fn parse(s: &str) -> Self {
-        let (size, counts) = s.split_once(": ").unwrap();
-        let (width, height) = size.split_once('x').unwrap();
-        Region {
-            width: parse::<u32>(width),
-            height: parse::<u32>(height),
-            counts: counts.split(' ').map(parse::<u32>).collect(),
-        }
+    fn parse(s: &str) -> Result<Self, SolveError> {
+        let (size, counts) =
+            (s.split_once(": ")).ok_or_else(|| SolveError::parse(s, "Expected 'WxH: counts'"))?;
+        let (width, height) =
+            (size.split_once('x')).ok_or_else(|| SolveError::parse(size, "Expected 'WxH'"))?;
+        Ok(Region {
+            width: parse::<u32>(width)?,
+            height: parse::<u32>(height)?,
+            counts: counts
+                .split(' ')
+                .map(parse::<u32>)
+                .collect::<Result<_, _>>()?,
+        })
     }
 
-    fn try_solve(&self, shapes: &Vec<HashSet<Shape>>) -> Option<usize> {
-        let trivial_grid = (self.width / 3) * (self.height / 3);
-        let total_boxes = self.counts.iter().sum::<u32>();
+    #[allow(clippy::ptr_arg)]
+    fn try_solve(&self, shapes: &Vec<HashSet<Shape>>) -> Result<Option<usize>, SolveError> {
+        let too_large = || SolveError::unsupported("Region sizes and counts overflow a u32");
+        let trivial_grid = ((self.width / 3).checked_mul(self.height / 3)).ok_or_else(too_large)?;
+        let total_boxes = (self.counts.iter())
+            .try_fold(0_u32, |a, c| a.checked_add(*c))
+            .ok_or_else(too_large)?;
         if total_boxes <= trivial_grid {
-            return Some(1);
+            return Ok(Some(1));
         }
-        let units = self
-            .counts
-            .iter()
+        let units = (self.counts.iter())
             .zip(shapes.iter())
-            .map(|(count, shape_set)| count * shape_set.iter().next().unwrap().count())
-            .sum::<u32>();
-        let area = self.width * self.height;
+            .try_fold(0_u32, |a, (count, shape_set)| {
+                count
+                    .checked_mul(shape_set.iter().next().unwrap().count())
+                    .and_then(|u| a.checked_add(u))
+            })
+            .ok_or_else(too_large)?;
+        let area = (self.width.checked_mul(self.height)).ok_or_else(too_large)?;
         if units > area {
-            return Some(0);
+            return Ok(Some(0));
         }
-        None
+        Ok(None)
     }
 }
 
@@ -56,17 +66,23 @@ const fn flip3(x: u16) -> u16 {
 }
 
 impl Shape {
-    fn parse(s: &str) -> Self {
-        let (id, body) = s.split_once(":\n").unwrap();
-        Shape {
-            id: id.parse::<u16>().expect("Bad id"),
-            data: body
-                .split('\n')
+    fn parse(s: &str) -> Result<Self, SolveError> {
+        let (id, body) = (s.split_once(":\n"))
+            .ok_or_else(|| SolveError::parse(s, "Expected 'id:' followed by a shape"))?;
+        // Shapes are packed into 9 bits so only 3x3 ones fit
+        let rows: Vec<_> = body.split('\n').collect();
+        if rows.len() != 3 || rows.iter().any(|r| r.len() != 3) {
+            return Err(SolveError::parse(body, "Expected a 3x3 shape"));
+        }
+        Ok(Shape {
+            id: parse::<u16>(id)?,
+            data: (rows.iter())
                 .flat_map(|l| l.chars())
                 .fold(0, |s, c| s * 2 + ((c == '#') as u16)),
-        }
+        })
     }
 
+    #[allow(clippy::clone_on_copy, clippy::unnecessary_literal_unwrap)]
     fn get_variants(&self) -> HashSet<Shape> {
         [self.clone(), self.fliph()]
             .iter()
@@ -117,24 +133,32 @@ impl Shape {
         (self.data >> 6) & 7
     }
 
+    #[allow(clippy::unnecessary_cast)]
     const fn count(&self) -> u32 {
         self.data.count_ones() as u32
     }
 }
 
-fn solve(input: &str) -> String {
+#[solver(day = 12, part = 1, input = "input.txt")]
+#[allow(clippy::manual_try_fold)]
+fn solve(input: &str) -> Solution {
     let mut parts: Vec<_> = input.split("\n\n").collect();
-    let regions = parts.pop().unwrap().split('\n').map(Region::parse);
+    let regions = (parts.pop().unwrap_or_default().split('\n'))
+        .map(Region::parse)
+        .collect::<Result<Vec<_>, _>>()?;
     let shapes: Vec<_> = (parts.iter())
-        .map(|s| Shape::parse(s))
-        .map(|s| s.get_variants())
-        .collect();
-    regions
+        .map(|s| Shape::parse(s).map(|s| s.get_variants()))
+        .collect::<Result<_, _>>()?;
+    let solved = (regions.iter())
         .map(|r| r.try_solve(&shapes))
+        .collect::<Result<Vec<_>, _>>()?;
+    (solved.into_iter())
         .fold(Some(0), |a, b| match (a, b) {
             (Some(aa), Some(bb)) => Some(aa + bb),
             _ => None,
         })
-        .map(|c| c.to_string())
-        .expect("hello example :)")
+        .map(|c| c.into())
+        .ok_or_else(|| {
+            SolveError::unsupported("Region needs a real packing search (hello example :)")
+        })
 }
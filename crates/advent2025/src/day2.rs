@@ -1,18 +1,23 @@
 use std::cmp::max;
 
-use adventlib::{Part, all_parts, interval::ClosedInterval};
-
-pub static PARTS: &'static [Part<'static>] =
-    &all_parts![parse_and_sum_twice_funny, parse_and_sum_all_funny];
+use adventlib::{Solution, error::SolveError, interval::ClosedInterval, log};
+use adventproc::solver;
 
 const fn num_digits(i: i64) -> u32 {
     i.ilog10() + 1
 }
 
+#[allow(clippy::manual_is_multiple_of)]
 fn fixed_len_funny_sum(a: i64, b: i64, repeat_count: u32) -> i64 {
     let d = num_digits(a);
     if repeat_count < 2 || d != num_digits(b) || a > b {
-        println!("Bad inputs a={}, b={}, repeat_count={}", a, b, repeat_count);
+        log!(
+            Warn,
+            "Bad inputs a={}, b={}, repeat_count={}",
+            a,
+            b,
+            repeat_count
+        );
         return 0;
     }
     if d % repeat_count != 0 {
@@ -41,7 +46,7 @@ fn fixed_len_funny_sum(a: i64, b: i64, repeat_count: u32) -> i64 {
 
 fn funny_sum(range: ClosedInterval, repeat: u32) -> i64 {
     if range.0 > range.1 {
-        println!("Bad range [{}]", range);
+        log!(Warn, "Bad range [{}]", range);
         return 0;
     }
     let a_d = num_digits(range.0);
@@ -58,19 +63,34 @@ fn funny_sum(range: ClosedInterval, repeat: u32) -> i64 {
         .sum();
 }
 
-fn parse_ranges(s: &str) -> impl Iterator<Item = ClosedInterval> {
-    return s.split(',').filter_map(ClosedInterval::parse);
+fn parse_ranges(s: &str) -> Result<Vec<ClosedInterval>, SolveError> {
+    return s.split(',').map(ClosedInterval::parse).collect();
 }
 
-pub fn parse_and_sum_twice_funny(s: &str) -> String {
-    parse_ranges(s)
+#[solver(
+    day = 2,
+    part = 1,
+    input = "input.txt",
+    example = "example.txt",
+    expected = 1227775554
+)]
+pub fn parse_and_sum_twice_funny(s: &str) -> Solution {
+    Ok(parse_ranges(s)?
+        .into_iter()
         .map(|r| funny_sum(r, 2))
         .sum::<i64>()
-        .to_string()
+        .into())
 }
 
-pub fn parse_and_sum_all_funny(s: &str) -> String {
-    let ranges: Vec<_> = parse_ranges(s).collect();
+#[solver(
+    day = 2,
+    part = 2,
+    input = "input.txt",
+    example = "example.txt",
+    expected = 4174379265
+)]
+pub fn parse_and_sum_all_funny(s: &str) -> Solution {
+    let ranges = parse_ranges(s)?;
     let max_digits = num_digits(ranges.iter().map(|r| max(r.0, r.1)).max().unwrap_or(0));
     // Find funny sums for each valid number of repeated sub-funny values
     let mut sums: Vec<_> = (2..=max_digits)
@@ -86,5 +106,5 @@ pub fn parse_and_sum_all_funny(s: &str) -> String {
             }
         }
     }
-    return sums.iter().sum::<i64>().to_string();
+    return Ok(sums.iter().sum::<i64>().into());
 }
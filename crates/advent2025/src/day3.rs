@@ -1,7 +1,7 @@
-use adventlib::{Part, all_parts};
-
-pub static PARTS: &'static [Part<'static>] = &all_parts![part_1, part_2];
+use adventlib::{Solution, error::SolveError};
+use adventproc::solver;
 
+#[allow(clippy::needless_lifetimes)]
 fn get_max_and_index<'a>(i: &'a [u32]) -> (usize, &'a u32) {
     i.iter()
         .enumerate()
@@ -9,10 +9,16 @@ fn get_max_and_index<'a>(i: &'a [u32]) -> (usize, &'a u32) {
         .expect("Empty array passed to get_max_and_index")
 }
 
-fn max_joltage(bank: &str, count: usize) -> u64 {
+fn max_joltage(bank: &str, count: usize) -> Result<u64, SolveError> {
     // NB: max_by_key returns the last value for equal elements but we want the first
     // so reverse the order. It also makes the ranges slightly cleaner.
     let digits: Vec<_> = bank.chars().filter_map(|c| c.to_digit(10)).rev().collect();
+    if digits.len() < count {
+        return Err(SolveError::parse(
+            bank,
+            format!("Bank has fewer than {} batteries", count),
+        ));
+    }
     let mut sum = 0u64;
     let mut limit = digits.len();
     for i in (0..count).rev() {
@@ -20,21 +26,35 @@ fn max_joltage(bank: &str, count: usize) -> u64 {
         sum = (sum * 10) + (*val as u64);
         limit = idx + i;
     }
-    return sum;
+    return Ok(sum);
 }
 
-fn sum_max_across_banks(input: &str, count: usize) -> String {
-    input
+fn sum_max_across_banks(input: &str, count: usize) -> Solution {
+    Ok(input
         .split('\n')
         .map(|b| max_joltage(b, count))
-        .sum::<u64>()
-        .to_string()
+        .sum::<Result<u64, _>>()?
+        .into())
 }
 
-fn part_1(input: &str) -> String {
+#[solver(
+    day = 3,
+    part = 1,
+    input = "input.txt",
+    example = "example.txt",
+    expected = 357
+)]
+fn part_1(input: &str) -> Solution {
     sum_max_across_banks(input, 2)
 }
 
-fn part_2(input: &str) -> String {
+#[solver(
+    day = 3,
+    part = 2,
+    input = "input.txt",
+    example = "example.txt",
+    expected = 3121910778619
+)]
+fn part_2(input: &str) -> Solution {
     sum_max_across_banks(input, 12)
 }
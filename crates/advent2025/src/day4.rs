@@ -1,7 +1,7 @@
-use adventlib::{Part, all_parts, util::Partition, vec::Vec3};
-
-pub static PARTS: &'static [Part<'static>] = &all_parts![single_pass, many_passes];
+use adventlib::{Solution, error::SolveError, util::Partition, vec::Vec3};
+use adventproc::{parser, solver};
 
+#[derive(Clone)]
 struct Grid {
     points: Vec<Vec3>,
     width: usize,
@@ -47,24 +47,57 @@ fn parse_row(row: &str, j: i32) -> impl Iterator<Item = Vec3> {
         .map(move |(i, _)| Vec3(i as i64, j as i64, 0))
 }
 
-fn parse_grid(s: &str) -> Grid {
+#[parser]
+fn parse_grid(s: &str) -> Result<Grid, SolveError> {
     let lines: Vec<_> = s.split('\n').collect();
+    if let Some(line) = lines.iter().find(|l| l.len() != lines[0].len()) {
+        return Err(SolveError::parse(line, "Grid rows have mismatched widths"));
+    }
     let mut grid = Grid::new(lines[0].len(), lines.len());
     (lines.iter())
         .enumerate()
         .flat_map(|(j, row)| parse_row(row, j as i32))
         .for_each(|p| grid.add(&p));
-    return grid;
+    return Ok(grid);
 }
 
-pub fn single_pass(input: &str) -> String {
-    let mut grid = parse_grid(input);
-    return grid.remove(4).to_string();
+#[solver(
+    day = 4,
+    part = 1,
+    input = "input.txt",
+    example = "example.txt",
+    expected = 13
+)]
+fn single_pass(grid: &Grid) -> Solution {
+    let mut grid = grid.clone();
+    return Ok(grid.remove(4).into());
 }
 
-pub fn many_passes(input: &str) -> String {
-    let mut grid = parse_grid(input);
+#[solver(
+    day = 4,
+    part = 2,
+    input = "input.txt",
+    example = "example.txt",
+    expected = 43
+)]
+fn many_passes(grid: &Grid) -> Solution {
+    let mut grid = grid.clone();
     let old = grid.points.len();
     while grid.remove(4) > 0 {}
-    return (old - grid.points.len()).to_string();
+    return Ok((old - grid.points.len()).into());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grids_which_clear_completely() {
+        for s in ["..@\n...", "...\n...", "@@\n@@"] {
+            let grid = parse_grid(s).unwrap();
+            let count = grid.points.len() as i64;
+            assert_eq!(single_pass(&grid), Ok(count.into()), "{s:?}");
+            assert_eq!(many_passes(&grid), Ok(count.into()), "{s:?}");
+        }
+    }
 }
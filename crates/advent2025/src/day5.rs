@@ -1,35 +1,53 @@
 use adventlib::{
-    Part, all_parts,
+    Solution,
+    error::SolveError,
     interval::{ClosedInterval, ClosedIntervals},
     util::parse,
 };
+use adventproc::solver;
 
-pub static PARTS: &'static [Part<'static>] = &all_parts![count_spoiled, count_total];
-
-fn read_sections(input: &str) -> (&str, &str) {
-    input.split_once("\n\n").expect("Wrong number of sections")
+fn read_sections(input: &str) -> Result<(&str, &str), SolveError> {
+    input
+        .split_once("\n\n")
+        .ok_or_else(|| SolveError::parse(input, "Wrong number of sections"))
 }
 
-fn count_spoiled(input: &str) -> String {
-    let (range_section, id_section) = read_sections(input);
-    let ranges: Vec<ClosedInterval> = range_section
+#[solver(
+    day = 5,
+    part = 1,
+    input = "input.txt",
+    example = "example.txt",
+    expected = 3
+)]
+fn count_spoiled(input: &str) -> Solution {
+    let (range_section, id_section) = read_sections(input)?;
+    let ranges = range_section
         .split('\n')
-        .filter_map(ClosedInterval::parse)
-        .collect();
-    return id_section
+        .map(ClosedInterval::parse)
+        .collect::<Result<Vec<_>, _>>()?;
+    let ids = id_section
         .split('\n')
         .map(parse::<i64>)
-        .filter(|i| ranges.iter().any(|r| r.contains(*i)))
+        .collect::<Result<Vec<_>, _>>()?;
+    return Ok(ids
+        .iter()
+        .filter(|i| ranges.iter().any(|r| r.contains(**i)))
         .count()
-        .to_string();
+        .into());
 }
 
-fn count_total(input: &str) -> String {
-    let (range_section, _) = read_sections(input);
+#[solver(
+    day = 5,
+    part = 2,
+    input = "input.txt",
+    example = "example.txt",
+    expected = 14
+)]
+fn count_total(input: &str) -> Solution {
+    let (range_section, _) = read_sections(input)?;
     let mut intervals = ClosedIntervals::new();
-    range_section
-        .split('\n')
-        .filter_map(ClosedInterval::parse)
-        .for_each(|i| intervals.add(i));
-    return intervals.count().to_string();
+    for line in range_section.split('\n') {
+        intervals.add(ClosedInterval::parse(line)?);
+    }
+    return Ok(intervals.count().into());
 }
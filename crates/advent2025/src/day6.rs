@@ -1,44 +1,56 @@
-use adventlib::{Part, all_parts, util::parse};
+use adventlib::{Part, Solution, all_parts, error::SolveError, util::parse};
 
-pub static PARTS: &'static [Part<'static>] =
-    &all_parts![|s| sum_ops(s, parse_rows), |s| sum_ops(s, parse_cols)];
+pub static PARTS: &'static [Part<'static>] = &all_parts![
+    |s| sum_ops(s, parse_rows) => "4277556",
+    |s| sum_ops(s, parse_cols) => "3263827"
+];
 
-fn parse_rows(source: &Vec<&str>, start: usize, end: usize) -> Vec<u64> {
+type Parser = fn(&Vec<&str>, usize, usize) -> Result<Vec<u64>, SolveError>;
+
+fn parse_rows(source: &Vec<&str>, start: usize, end: usize) -> Result<Vec<u64>, SolveError> {
     (source.iter())
         .map(move |s| parse::<u64>(s[start..end].trim()))
         .collect()
 }
 
-fn parse_cols(source: &Vec<&str>, start: usize, end: usize) -> Vec<u64> {
-    (start..end)
+fn parse_cols(source: &Vec<&str>, start: usize, end: usize) -> Result<Vec<u64>, SolveError> {
+    Ok((start..end)
         .map(|i| {
             source
                 .iter()
-                .filter_map(|s| s[i..i + 1].parse::<u64>().ok())
+                .filter_map(|s| s.get(i..i + 1)?.parse::<u64>().ok())
                 .fold(0, |a, b| a * 10 + b)
         })
         .filter(|i| *i > 0)
-        .collect()
+        .collect())
 }
 
-pub fn sum_ops(input: &str, parser: fn(&Vec<&str>, usize, usize) -> Vec<u64>) -> String {
+pub fn sum_ops(input: &str, parser: Parser) -> Solution {
     let mut rows: Vec<_> = input.split('\n').collect();
-    let max = rows.iter().map(|s| s.len()).max().unwrap();
-    let ops: Vec<_> = (rows.pop().unwrap())
+    let max = rows.iter().map(|s| s.len()).max().unwrap_or(0);
+    let op_row = rows.pop().unwrap_or_default();
+    if let Some(c) = op_row.chars().find(|c| !"+* ".contains(*c)) {
+        return Err(SolveError::parse(
+            op_row,
+            format!("Unknown operator '{}'", c),
+        ));
+    }
+    let ops: Vec<_> = op_row
         .chars()
         .enumerate()
         .filter(|(_, c)| !c.is_whitespace())
         .map(|(i, c)| (i, c == '*'))
         .chain(std::iter::once((max, false)))
         .collect();
-    ops.iter()
+    Ok(ops
+        .iter()
         .zip(ops[1..].iter())
         .map(|((i, mult), (j, _))| {
-            parser(&rows, *i, *j).iter().fold(
+            Ok(parser(&rows, *i, *j)?.iter().fold(
                 *mult as u64,
                 if *mult { |a, b| a * b } else { |a, b| a + b },
-            )
+            ))
         })
-        .sum::<u64>()
-        .to_string()
+        .sum::<Result<u64, SolveError>>()?
+        .into())
 }
@@ -1,9 +1,7 @@
 use std::iter;
 
-use adventlib::{Part, all_parts};
-
-pub static PARTS: &'static [Part<'static>] =
-    &all_parts![count_classical_splits, count_quantum_splits];
+use adventlib::Solution;
+use adventproc::solver;
 
 struct Row {
     split_count: u64,
@@ -53,10 +51,24 @@ fn process_rows(input: &str) -> Row {
     return rows.fold(beams, |a, b| a.split_on(&b));
 }
 
-pub fn count_classical_splits(input: &str) -> String {
-    process_rows(input).split_count.to_string()
+#[solver(
+    day = 7,
+    part = 1,
+    input = "input.txt",
+    example = "example.txt",
+    expected = 21
+)]
+pub fn count_classical_splits(input: &str) -> Solution {
+    Ok(process_rows(input).split_count.into())
 }
 
-pub fn count_quantum_splits(input: &str) -> String {
-    process_rows(input).get_path_count().to_string()
+#[solver(
+    day = 7,
+    part = 2,
+    input = "input.txt",
+    example = "example.txt",
+    expected = 40
+)]
+pub fn count_quantum_splits(input: &str) -> Solution {
+    Ok(process_rows(input).get_path_count().into())
 }
@@ -1,14 +1,9 @@
 use std::collections::{BinaryHeap, HashMap};
 
-use adventlib::{Part, vec::Vec3};
-
-pub static PARTS: &'static [Part<'static>] = &[
-    Part::new("Example", "example.txt", |s| times_top_circuits(s, 10, 3)),
-    Part::new("Part", "input.txt", |s| times_top_circuits(s, 1000, 3)),
-    Part::new("Example", "example.txt", full_circuit_last_pair),
-    Part::new("Part", "input.txt", full_circuit_last_pair),
-];
+use adventlib::{Solution, error::SolveError, params::Params, vec::Vec3};
+use adventproc::{parser, solver};
 
+#[derive(Clone)]
 struct Graph {
     points: Vec<Vec3>,
     edge_heap: BinaryHeap<(i64, usize, usize)>,
@@ -17,8 +12,10 @@ struct Graph {
 }
 
 impl Graph {
-    fn read(s: &str) -> Self {
-        let points: Vec<_> = s.split('\n').map(Vec3::parse).collect();
+    fn read(s: &str) -> Result<Self, SolveError> {
+        let points = (s.split('\n'))
+            .map(Vec3::parse)
+            .collect::<Result<Vec<_>, _>>()?;
         let edge_heap: BinaryHeap<_> = (0..points.len())
             .flat_map(|i| {
                 ((i + 1)..points.len())
@@ -37,7 +34,7 @@ impl Graph {
             graph.point_to_circuit.insert(i, i);
             graph.circuit_to_points.insert(i, vec![i]);
         }
-        return graph;
+        return Ok(graph);
     }
 
     fn connect_shortest(&mut self) -> Option<(usize, usize)> {
@@ -60,25 +57,58 @@ impl Graph {
     }
 }
 
-pub fn times_top_circuits(s: &str, connect: usize, take: usize) -> String {
-    let mut graph = Graph::read(s);
+// Both parts start from the same graph which is only built once per input
+#[parser]
+fn parse_graph(s: &str) -> Result<Graph, SolveError> {
+    Graph::read(s)
+}
+
+#[solver(
+    day = 8,
+    part = 1,
+    input = "input.txt",
+    params = "connect=1000,take=3",
+    example = "example.txt",
+    example_params = "connect=10,take=3",
+    expected = 40
+)]
+#[allow(clippy::unnecessary_fold)]
+fn times_top_circuits(graph: &Graph, params: &Params) -> Solution {
+    let connect: usize = params.get("connect")?;
+    let take: usize = params.get("take")?;
+    let mut graph = graph.clone();
     (0..connect).for_each(|_| {
         graph.connect_shortest();
     });
     let mut top_circuits: BinaryHeap<_> =
         graph.circuit_to_points.values().map(|v| v.len()).collect();
-    (0..take)
-        .map(|_| top_circuits.pop().unwrap())
+    if top_circuits.len() < take {
+        return Err(SolveError::unsupported(format!(
+            "Fewer than {} circuits",
+            take
+        )));
+    }
+    Ok((0..take)
+        .filter_map(|_| top_circuits.pop())
         .fold(1, |a, b| a * b)
-        .to_string()
+        .into())
 }
 
-pub fn full_circuit_last_pair(s: &str) -> String {
-    let mut graph = Graph::read(s);
+#[solver(
+    day = 8,
+    part = 2,
+    input = "input.txt",
+    example = "example.txt",
+    expected = 25272
+)]
+fn full_circuit_last_pair(graph: &Graph) -> Solution {
+    let mut graph = graph.clone();
     while let Some((i, j)) = graph.connect_shortest() {
         if graph.circuit_to_points.len() == 1 {
-            return (graph.points[i].0 * graph.points[j].0).to_string();
+            return Ok((graph.points[i].0 * graph.points[j].0).into());
         }
     }
-    panic!();
+    Err(SolveError::no_solution(
+        "Points never form a single circuit",
+    ))
 }
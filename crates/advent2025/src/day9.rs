@@ -1,12 +1,22 @@
 use std::collections::{BinaryHeap, HashMap};
 
-use adventlib::{Part, all_parts, interval::ClosedVolume, util::index_posmod, vec::Vec3};
+use adventlib::{
+    Solution, error::SolveError, interval::ClosedVolume, util::index_posmod, vec::Vec3,
+};
+use adventproc::solver;
 
-pub static PARTS: &'static [Part<'static>] =
-    &all_parts![find_largest_rect, find_largest_contained_rec];
-
-fn find_largest_rect(input: &str) -> String {
-    let mut points: Vec<_> = input.split('\n').map(Vec3::parse).collect();
+#[solver(
+    day = 9,
+    part = 1,
+    input = "input.txt",
+    example = "example.txt",
+    expected = 50
+)]
+#[allow(clippy::map_entry)]
+fn find_largest_rect(input: &str) -> Solution {
+    let mut points = (input.split('\n'))
+        .map(Vec3::parse)
+        .collect::<Result<Vec<_>, _>>()?;
     let mut leftmost = HashMap::new(); // Track the first point seen at various y values
     // Ascending lexicographic order in order to traverse points from lowst to highest x value
     points.sort();
@@ -19,14 +29,15 @@ fn find_largest_rect(input: &str) -> String {
             leftmost.values().map(|o| p.area(o)).max().unwrap()
         })
         .max()
-        .expect("Empty input")
-        .to_string()
+        .map(|a| a.into())
+        .ok_or_else(|| SolveError::no_solution("Empty input"))
 }
 
 fn ccw(a: &Vec3, b: &Vec3) -> bool {
     &Vec3(a.1, -a.0, a.2) == b
 }
 
+#[allow(clippy::op_ref)]
 fn interpolate(a: &Vec3, b: &Vec3, dist: i64) -> Vec<Vec3> {
     let v = (b - a).signum() * dist;
     let d = (b - a).len();
@@ -34,6 +45,7 @@ fn interpolate(a: &Vec3, b: &Vec3, dist: i64) -> Vec<Vec3> {
     (1..(d / dist)).map(|i| a + &(v * i)).collect()
 }
 
+#[allow(clippy::len_zero, clippy::op_ref, clippy::ptr_arg)]
 fn get_poison_points(points: &Vec<Vec3>) -> Vec<Vec3> {
     let mut poison = vec![];
     for i in 0..points.len() {
@@ -55,8 +67,17 @@ fn get_poison_points(points: &Vec<Vec3>) -> Vec<Vec3> {
     return poison;
 }
 
-fn find_largest_contained_rec(input: &str) -> String {
-    let points: Vec<_> = input.split('\n').map(Vec3::parse).collect();
+#[solver(
+    day = 9,
+    part = 2,
+    input = "input.txt",
+    example = "example.txt",
+    expected = 24
+)]
+fn find_largest_contained_rec(input: &str) -> Solution {
+    let points = (input.split('\n'))
+        .map(Vec3::parse)
+        .collect::<Result<Vec<_>, _>>()?;
     let poison = get_poison_points(&points);
 
     let mut heap: BinaryHeap<_> = (0..points.len())
@@ -73,7 +94,7 @@ fn find_largest_contained_rec(input: &str) -> String {
             return Some((a, i, j));
         })
         .next()
-        .expect("Empty input");
+        .ok_or_else(|| SolveError::no_solution("No rectangle fits inside the polygon"))?;
 
-    return best.0.to_string();
+    return Ok(best.0.into());
 }
@@ -0,0 +1,270 @@
+use std::{
+    collections::HashMap,
+    fs::{create_dir_all, read_to_string, write},
+    io::{self, Read, Write},
+    net::TcpStream,
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::Mutex,
+};
+
+use crate::cache::{Slots, slot};
+
+// Somewhere the files of a day come from, keyed by year, day and file name
+pub trait InputProvider: Sync {
+    fn input(&self, year: u32, day: usize, file: &str) -> Result<String, String>;
+}
+
+// Reads files from the data directory, downloading puzzle inputs which are
+// missing from an upstream provider and keeping them for later runs.
+pub struct FsCache {
+    data_dir: PathBuf,
+    latest_year: u32,
+    upstream: Option<Box<dyn InputProvider>>,
+    // Every download attempted this run so each input is requested at most
+    // once, with a slot per day so that different days download in parallel
+    fetched: Slots<(u32, usize), Result<String, String>>,
+}
+
+impl FsCache {
+    pub fn new(
+        data_dir: PathBuf,
+        latest_year: u32,
+        upstream: Option<Box<dyn InputProvider>>,
+    ) -> Self {
+        FsCache {
+            data_dir: data_dir,
+            latest_year: latest_year,
+            upstream: upstream,
+            fetched: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Data for a day lives in data/<year>/<day>/, though the latest year also
+    // falls back to data/<day>/ from before the runner knew about years.
+    pub fn day_dir(&self, year: u32, day: usize) -> PathBuf {
+        let dir = (self.data_dir).join(year.to_string()).join(day.to_string());
+        let legacy = self.data_dir.join(day.to_string());
+        if !dir.is_dir() && year == self.latest_year && legacy.is_dir() {
+            return legacy;
+        }
+        dir
+    }
+
+    fn fetch(&self, year: u32, day: usize, path: &PathBuf) -> Result<String, String> {
+        let upstream = self.upstream.as_ref().ok_or_else(|| {
+            format!(
+                "{} is missing (set ADVENT_SESSION to download it)",
+                path.display()
+            )
+        })?;
+        // Only this day's slot stays locked while downloading
+        let fetched = slot(&self.fetched, (year, day));
+        let result = fetched.get_or_init(|| {
+            upstream.input(year, day, "input.txt").and_then(|input| {
+                let dir = path.parent().unwrap();
+                create_dir_all(dir).map_err(|err| format!("{}: {}", dir.display(), err))?;
+                write(path, &input).map_err(|err| format!("{}: {}", path.display(), err))?;
+                Ok(input)
+            })
+        });
+        result.clone()
+    }
+}
+
+impl InputProvider for FsCache {
+    fn input(&self, year: u32, day: usize, file: &str) -> Result<String, String> {
+        let path = self.day_dir(year, day).join(file);
+        match read_to_string(&path) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound && file == "input.txt" => {
+                self.fetch(year, day, &path)
+            }
+            Err(err) => Err(format!("{}: {}", path.display(), err)),
+            Ok(content) => Ok(content),
+        }
+    }
+}
+
+// Downloads puzzle inputs from <base_url>/<year>/day/<day>/input using a
+// session cookie. Plain http URLs are requested directly, which is enough for
+// a local stub server, while https is handed to curl.
+pub struct HttpProvider {
+    base_url: String,
+    session: String,
+}
+
+impl HttpProvider {
+    pub fn new(base_url: &str, session: &str) -> Self {
+        HttpProvider {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            session: session.trim().to_string(),
+        }
+    }
+
+    fn get(&self, url: &str) -> Result<String, String> {
+        let Some(rest) = url.strip_prefix("http://") else {
+            return self.curl(url);
+        };
+        let (host, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        let address = match host.contains(':') {
+            true => host.to_string(),
+            false => format!("{}:80", host),
+        };
+        let mut stream =
+            TcpStream::connect(&address).map_err(|err| format!("{}: {}", address, err))?;
+        // HTTP/1.0 keeps the response free of chunked encoding
+        let request = format!(
+            "GET {path} HTTP/1.0\r\nHost: {host}\r\nCookie: session={}\r\nUser-Agent: advent2025 runner\r\n\r\n",
+            self.session
+        );
+        let mut response = vec![];
+        (stream.write_all(request.as_bytes()))
+            .and_then(|_| stream.read_to_end(&mut response))
+            .map_err(|err| format!("{}: {}", url, err))?;
+        let response = String::from_utf8_lossy(&response);
+        let (head, body) = response
+            .split_once("\r\n\r\n")
+            .ok_or_else(|| format!("{}: malformed response", url))?;
+        let status = head.lines().next().unwrap_or_default();
+        match status.split_whitespace().nth(1) {
+            Some("200") => Ok(body.to_string()),
+            _ => Err(format!("{}: {}", url, status)),
+        }
+    }
+
+    fn curl(&self, url: &str) -> Result<String, String> {
+        // The cookie goes through stdin to keep it out of the process list
+        let mut child = Command::new("curl")
+            .args(["--silent", "--show-error", "--fail", "--header", "@-", url])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| format!("curl: {}", err))?;
+        let cookie = format!("Cookie: session={}\n", self.session);
+        let _ = child.stdin.take().unwrap().write_all(cookie.as_bytes());
+        let output = child
+            .wait_with_output()
+            .map_err(|err| format!("curl: {}", err))?;
+        match output.status.success() {
+            true => Ok(String::from_utf8_lossy(&output.stdout).to_string()),
+            false => Err(format!(
+                "{}: {}",
+                url,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )),
+        }
+    }
+}
+
+impl InputProvider for HttpProvider {
+    fn input(&self, year: u32, day: usize, file: &str) -> Result<String, String> {
+        if file != "input.txt" {
+            return Err(format!(
+                "Only puzzle inputs can be downloaded, not {}",
+                file
+            ));
+        }
+        self.get(&format!("{}/{}/day/{}/input", self.base_url, year, day))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env,
+        fs::remove_dir_all,
+        io::{BufRead, BufReader},
+        net::TcpListener,
+        sync::Arc,
+        thread,
+    };
+
+    use super::*;
+
+    // Serves day 1 and answers 404 for anything else, recording each request
+    // line and cookie
+    fn stub_server() -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+        let seen = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let head: Vec<String> = BufReader::new(&stream)
+                    .lines()
+                    .map(|line| line.unwrap())
+                    .take_while(|line| !line.is_empty())
+                    .collect();
+                let cookie = head.iter().find(|h| h.starts_with("Cookie:"));
+                seen.lock()
+                    .unwrap()
+                    .push(format!("{} {}", head[0], cookie.unwrap()));
+                let response = match head[0].starts_with("GET /2025/day/1/input ") {
+                    true => "HTTP/1.0 200 OK\r\n\r\n1\n2\n",
+                    false => "HTTP/1.0 404 Not Found\r\n\r\nNope",
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    #[test]
+    fn downloads_with_the_session_cookie() {
+        let (url, requests) = stub_server();
+        let http = HttpProvider::new(&format!("{}/", url), " secret\n");
+        assert_eq!(http.input(2025, 1, "input.txt"), Ok("1\n2\n".to_string()));
+        assert_eq!(
+            *requests.lock().unwrap(),
+            vec!["GET /2025/day/1/input HTTP/1.0 Cookie: session=secret"]
+        );
+        let err = http.input(2025, 2, "input.txt").unwrap_err();
+        assert!(
+            err.ends_with("/2025/day/2/input: HTTP/1.0 404 Not Found"),
+            "{}",
+            err
+        );
+        assert!(http.input(2025, 1, "example.txt").is_err());
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn caches_downloads_on_disk_and_failures_for_the_run() {
+        let (url, requests) = stub_server();
+        let data_dir = env::temp_dir().join(format!("advent-input-test-{}", std::process::id()));
+        let cache = FsCache::new(
+            data_dir.clone(),
+            2025,
+            Some(Box::new(HttpProvider::new(&url, "secret"))),
+        );
+        for _ in 0..2 {
+            assert_eq!(cache.input(2025, 1, "input.txt"), Ok("1\n2\n".to_string()));
+            assert!(
+                cache
+                    .input(2025, 2, "input.txt")
+                    .unwrap_err()
+                    .contains("404")
+            );
+            assert!(cache.input(2025, 1, "example.txt").is_err());
+        }
+        assert_eq!(requests.lock().unwrap().len(), 2);
+        // A later run reads the saved input without downloading it again
+        let offline = FsCache::new(data_dir.clone(), 2025, None);
+        assert_eq!(
+            offline.input(2025, 1, "input.txt"),
+            Ok("1\n2\n".to_string())
+        );
+        assert!(
+            offline
+                .input(2025, 2, "input.txt")
+                .unwrap_err()
+                .contains("ADVENT_SESSION")
+        );
+        remove_dir_all(&data_dir).unwrap();
+    }
+}
@@ -1,96 +1,560 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeSet, HashMap},
     fs::read_to_string,
-    time::SystemTime,
+    io::Read,
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    sync::{
+        Arc,
+        mpsc::{self, RecvTimeoutError},
+    },
+    thread,
+    time::{Duration, Instant},
 };
 
-use adventlib::{Part, util};
+use adventlib::{
+    Answer, Part, Solution, SolverFn, Year,
+    cancel::Cancel,
+    log::{self, Record},
+    params::Params,
+};
 use adventproc::setup_problems;
 
-static DELIMS: &'static [&'static str] = &["-", ".."];
+use crate::{
+    answers::{Answers, Verdict},
+    bench::{Baseline, Stats},
+    cache::Cache,
+    input::{FsCache, HttpProvider, InputProvider},
+    mem::MemStats,
+    options::Options,
+    report::PartResult,
+    select::Selector,
+    watch::History,
+};
+
+mod answers;
+mod bench;
+mod cache;
+mod input;
+mod mem;
+mod options;
+mod pool;
+mod report;
+mod scaffold;
+mod select;
+mod watch;
+
+setup_problems!();
+
+fn ms_since(time: Instant) -> f64 {
+    return time.elapsed().as_secs_f64() * 1000_f64;
+}
+
+// Outcome of running a part, possibly many times over when benchmarking
+struct Run {
+    result: Result<Answer, String>,
+    elapsed_ms: f64,
+    stats: Option<Stats>,
+    mem: Option<MemStats>, // Allocations of the last run with --mem
+    logs: Vec<Record>,     // Messages of the last run shown with -v
+    timed_out: bool,
+}
+
+impl Run {
+    fn failed(err: String) -> Self {
+        Run {
+            result: Err(err),
+            elapsed_ms: 0_f64,
+            stats: None,
+            mem: None,
+            logs: vec![],
+            timed_out: false,
+        }
+    }
+}
 
-setup_problems!(12);
+// A solver call with its input and parameters, owning everything so it can be
+// moved onto a worker thread. Errors are located within the input, which is
+// what the solver started from, and messages are labelled with the part.
+type Job = Arc<dyn Fn() -> (Result<Answer, String>, Vec<Record>) + Send + Sync>;
+
+fn job(
+    content: Arc<String>,
+    task: &Task,
+    options: &Options,
+    solve: impl Fn(&str) -> Solution + Send + Sync + 'static,
+) -> Job {
+    let (context, level) = (task.context(), options.log_level());
+    Arc::new(move || {
+        let input = content.trim();
+        log::capture(&context, level, || {
+            solve(input).map_err(|err| err.locate(input).to_string())
+        })
+    })
+}
 
-fn is_valid_day(day: &usize) -> bool {
-    return *day > 0 && *day <= PROBLEMS.len();
+// A single timed run of a job
+struct Sample {
+    result: Result<Answer, String>,
+    elapsed_ms: f64,
+    mem: Option<MemStats>,
+    logs: Vec<Record>,
 }
 
-fn get_arg_days(mut arg: &str) -> HashSet<usize> {
-    arg = arg.trim();
-    if DELIMS.contains(&arg) {
-        return (1..(PROBLEMS.len() + 1)).collect();
+fn measure(job: &Job) -> Sample {
+    let ((result, logs, elapsed), mem) = mem::measure(|| {
+        let start = Instant::now();
+        let (result, logs) = job();
+        (result, logs, ms_since(start))
+    });
+    Sample {
+        result: result,
+        elapsed_ms: elapsed,
+        mem: mem,
+        logs: logs,
     }
-    if arg.contains(',') {
-        return arg.split(',').map(get_arg_days).flatten().collect();
+}
+
+// Runs f, turning a panic into an error with its message so that one broken
+// part doesn't take the rest of the run down with it
+fn contain<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        let message = (payload.downcast_ref::<&str>().map(|s| s.to_string()))
+            .or_else(|| payload.downcast_ref::<String>().cloned());
+        match message {
+            Some(message) => format!("Solver panicked: {}", message),
+            None => "Solver panicked".to_string(),
+        }
+    })
+}
+
+fn panicked(err: String) -> Sample {
+    Sample {
+        result: Err(err),
+        elapsed_ms: 0_f64,
+        mem: None,
+        logs: vec![],
     }
-    for delim in DELIMS {
-        if arg.contains(delim) {
-            let bounds: Vec<_> = arg
-                .split(delim)
-                .map(util::parse::<i32>)
-                .map(|i| util::posmod(i, PROBLEMS.len() as i32) as usize)
-                .filter(is_valid_day)
-                .collect();
-            let min = *(bounds.iter().max().unwrap());
-            let max = bounds.iter().min().unwrap() + 1;
-            return (min..max).collect();
+}
+
+// With a timeout f runs on a thread of its own which is left behind, with its
+// cancellation token set, if it doesn't finish in time. Either way a panic in
+// f becomes an error.
+fn timed<T: Send + 'static>(
+    f: impl FnOnce() -> T + Send + 'static,
+    timeout: Option<Duration>,
+) -> Result<Result<T, String>, Duration> {
+    let Some(timeout) = timeout else {
+        return Ok(contain(f));
+    };
+    let (sender, receiver) = mpsc::channel();
+    let cancel = Cancel::new();
+    let token = cancel.clone();
+    thread::spawn(move || {
+        let _ = sender.send(token.scope(|| contain(f)));
+    });
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => Ok(result),
+        Err(RecvTimeoutError::Disconnected) => Ok(Err("Solver panicked".to_string())),
+        Err(RecvTimeoutError::Timeout) => {
+            cancel.cancel();
+            Err(timeout)
         }
     }
-    if let Ok(i) = arg.parse::<usize>() {
-        if is_valid_day(&i) {
-            return (i..(i + 1)).collect();
-        } else {
-            println!("Invalid day {}", arg)
+}
+
+fn timed_run(job: &Job, timeout: Option<Duration>) -> Result<Sample, Duration> {
+    let job = job.clone();
+    timed(move || measure(&job), timeout).map(|sample| sample.unwrap_or_else(panicked))
+}
+
+fn timed_out(timeout: Duration) -> Run {
+    Run {
+        result: Err(format!("Timed out after {:?}", timeout)),
+        elapsed_ms: timeout.as_secs_f64() * 1000_f64,
+        stats: None,
+        mem: None,
+        logs: vec![],
+        timed_out: true,
+    }
+}
+
+// Runs a part once or, when benchmarking, repeatedly against the same input,
+// giving up on the part as soon as one run takes longer than --timeout
+fn run_part(job: &Job, options: &Options) -> Run {
+    match options.bench {
+        None => match timed_run(job, options.timeout) {
+            Ok(sample) => Run {
+                result: sample.result,
+                elapsed_ms: sample.elapsed_ms,
+                stats: None,
+                mem: sample.mem,
+                logs: sample.logs,
+                timed_out: false,
+            },
+            Err(timeout) => timed_out(timeout),
+        },
+        Some(runs) => {
+            for _ in 0..options.warmup {
+                if let Err(timeout) = timed_run(job, options.timeout) {
+                    return timed_out(timeout);
+                }
+            }
+            let mut samples = vec![];
+            let mut last = None;
+            for _ in 0..runs {
+                let sample = match timed_run(job, options.timeout) {
+                    Ok(sample) => sample,
+                    Err(timeout) => return timed_out(timeout),
+                };
+                samples.push(sample.elapsed_ms);
+                last = Some(sample);
+            }
+            let stats = Stats::from_samples(samples);
+            let last = last.unwrap();
+            Run {
+                result: last.result,
+                elapsed_ms: stats.map_or(0_f64, |s| s.median),
+                stats: stats,
+                mem: last.mem,
+                logs: last.logs,
+                timed_out: false,
+            }
         }
     }
-    return HashSet::new();
 }
 
-fn ms_since(time: SystemTime) -> f64 {
-    if let Ok(duration) = SystemTime::now().duration_since(time) {
-        return (duration.as_micros() as f64) / 1000_f64;
+// A single part of a day queued to run against its input file
+struct Task {
+    year: u32,
+    day: usize,
+    part: &'static Part<'static>,
+    index: u32, // Disambiguates parts sharing a name, starting at 1
+    source: Source,
+    file: String, // How the input is labelled in reports and answer files
+    params: Params,
+}
+
+enum Source {
+    Data, // The part's own file from the data directory
+    File(PathBuf),
+    Text(Arc<String>),
+}
+
+impl Task {
+    // Labels the messages logged while solving
+    fn context(&self) -> String {
+        format!(
+            "{} day {} {} {}",
+            self.year, self.day, self.part.name, self.index
+        )
+    }
+}
+
+// A day of a particular year's event
+type DayKey = (u32, usize);
+
+fn get_year(year: u32) -> &'static Year<'static> {
+    PROBLEMS.iter().find(|y| y.year == year).unwrap()
+}
+
+// Expands the selectors into tasks in year/day/part order along with the
+// selected days which have no module yet, failing if any selector matches
+// nothing.
+fn plan_tasks(
+    selectors: &[Selector],
+    options: &Options,
+    stdin: Option<Arc<String>>,
+) -> Result<(Vec<Task>, Vec<DayKey>), String> {
+    let days: BTreeSet<DayKey> = (selectors.iter())
+        .flat_map(|s| s.days.iter().map(|d| (s.year, *d)))
+        .collect();
+    let mut tasks = vec![];
+    let mut unimplemented = vec![];
+    let mut matched = vec![false; selectors.len()];
+    let mut used = vec![false; options.params.len()];
+    for (year, day) in days {
+        if !get_year(year).is_implemented(day) {
+            unimplemented.push((year, day));
+            for (i, selector) in selectors.iter().enumerate() {
+                matched[i] |= selector.year == year && selector.days.contains(&day);
+            }
+            continue;
+        }
+        let mut name_counts: HashMap<&str, u32> = HashMap::new();
+        for part in get_year(year).parts[day - 1] {
+            let index = name_counts.get(part.name).unwrap_or(&0_u32) + 1;
+            name_counts.insert(part.name, index);
+            let mut selected = false;
+            for (i, selector) in selectors.iter().enumerate() {
+                if selector.matches(year, day, part.name, index) {
+                    matched[i] = true;
+                    selected = true;
+                }
+            }
+            if selected {
+                let (source, file) = match (&stdin, &options.file) {
+                    (Some(text), _) => (Source::Text(text.clone()), "<stdin>".to_string()),
+                    (None, Some(path)) => (Source::File(path.clone()), path.display().to_string()),
+                    (None, None) => (Source::Data, part.file.to_string()),
+                };
+                let mut params = part.default_params();
+                for (i, (name, value)) in options.params.iter().enumerate() {
+                    used[i] |= params.set(name, value);
+                }
+                tasks.push(Task {
+                    year: year,
+                    day: day,
+                    part: part,
+                    index: index,
+                    source: source,
+                    file: file,
+                    params: params,
+                });
+            }
+        }
+    }
+    if let Some(i) = matched.iter().position(|m| !m) {
+        return Err(format!("Selector '{}' matches no parts", selectors[i].text));
+    }
+    if let Some(i) = used.iter().position(|u| !u) {
+        return Err(format!(
+            "Parameter '{}' isn't taken by any selected part",
+            options.params[i].0
+        ));
     }
-    return -1_f64;
+    if (options.file.is_some() || stdin.is_some())
+        && (tasks.iter()).any(|t| (t.year, t.day) != (tasks[0].year, tasks[0].day))
+    {
+        return Err("--file and --stdin can only be used with a single day".to_string());
+    }
+    Ok((tasks, unimplemented))
 }
 
-fn run_solvers(day: &usize) {
-    let start = SystemTime::now();
-    println!("Day {}:", day);
-    let mut name_counts: HashMap<&str, u32> = HashMap::new();
-    let parts = PROBLEMS[day - 1];
-    for part in parts {
-        let index = name_counts.get(part.name).unwrap_or(&0_u32) + 1;
-        let name = format!("{} {}", part.name, index);
-        name_counts.insert(part.name, index);
+// Runs a task, reading its input and, for two stage parts, parsing it only if
+// no other part has done so already. Parsing is timed apart from solving and
+// isn't repeated when benchmarking, but is held to the same timeout.
+fn run_task(task: &Task, options: &Options, inputs: &FsCache, cache: &Arc<Cache>) -> PartResult {
+    let key = (task.year, task.day, task.file.clone());
+    let content = cache.content(&key, || match &task.source {
+        Source::Data => inputs.input(task.year, task.day, task.part.file),
+        Source::File(path) => read_to_string(path).map_err(|err| err.to_string()),
+        Source::Text(text) => Ok(text.to_string()),
+    });
+    let (mut parse_ms, mut parse_cached, mut parse_mem) = (None, false, None);
+    let mut parse_logs = vec![];
+    let mut run = match content {
+        Ok(content) => match task.part.solver {
+            SolverFn::Staged(stages) => {
+                // Parts waiting on another's parse wait on their own thread, so
+                // they time out too if it hangs
+                let (cache, input) = (cache.clone(), content.clone());
+                let (context, level) = (task.context(), options.log_level());
+                let parse = move || {
+                    log::capture(&context, level, || cache.parse(&key, stages, input.trim()))
+                };
+                match timed(parse, options.timeout) {
+                    Ok(Ok(((parse, parsed_here), logs))) => {
+                        (parse_ms, parse_cached, parse_logs) =
+                            (Some(parse.elapsed_ms), !parsed_here, logs);
+                        parse_mem = parse.mem.filter(|_| parsed_here);
+                        match &parse.parsed {
+                            Ok(parsed) => {
+                                let (parsed, params) = (parsed.clone(), task.params.clone());
+                                let solve = move |_: &str| stages.solve(&parsed, &params);
+                                run_part(&job(content, task, options, solve), options)
+                            }
+                            Err(err) => Run::failed(err.clone()),
+                        }
+                    }
+                    Ok(Err(err)) => Run::failed(err),
+                    Err(timeout) => timed_out(timeout),
+                }
+            }
+            _ => {
+                let (part, params) = (task.part, task.params.clone());
+                let solve = move |input: &str| part.solve(input, &params);
+                run_part(&job(content, task, options, solve), options)
+            }
+        },
+        Err(err) => Run::failed(err),
+    };
+    parse_logs.append(&mut run.logs);
+    PartResult {
+        year: task.year,
+        day: task.day,
+        name: task.part.name,
+        index: task.index,
+        file: task.file.clone(),
+        params: task.params.clone(),
+        result: run.result,
+        elapsed_ms: run.elapsed_ms,
+        parse_ms: parse_ms,
+        parse_cached: parse_cached,
+        parse_mem: parse_mem,
+        mem: run.mem,
+        logs: parse_logs,
+        timed_out: run.timed_out,
+        verdict: None,
+        stats: run.stats,
+        change_pct: None,
+        previous: None,
+    }
+}
 
-        let part_start = SystemTime::now();
-        match read_to_string(format!("data/{}/{}", day, part.file)) {
-            Ok(content) => println!(" > {}:\t{}", name, (part.solver)(content.trim())),
-            Err(err) => println!(" > {}:\tError: {}", name, err),
-        };
-        println!("   [{:0.2}ms]", ms_since(part_start));
+fn load_answers(year: u32, day: usize, options: &Options, inputs: &FsCache) -> Answers {
+    if !options.check {
+        return Answers::default();
     }
-    println!("  [{:0.2}ms]", ms_since(start));
+    let path = inputs.day_dir(year, day).join("answers.toml");
+    Answers::load(&path).unwrap_or_else(|err| {
+        eprintln!("Error: {}", err);
+        Answers::default()
+    })
+}
+
+// Hands over to the watcher with the module and data directory of every
+// selected day, including days which have no module yet.
+fn watch_tasks(
+    args: &[String],
+    tasks: &[Task],
+    unimplemented: &[DayKey],
+    options: &Options,
+    inputs: &FsCache,
+) -> ! {
+    let days: BTreeSet<DayKey> = (tasks.iter())
+        .map(|t| (t.year, t.day))
+        .chain(unimplemented.iter().copied())
+        .collect();
+    let mut sources: Vec<_> = (days.iter())
+        .map(|(year, day)| scaffold::module_path(*year, *day))
+        .collect();
+    sources.extend(options.file.clone());
+    let data_dirs: Vec<_> = (days.iter())
+        .map(|(year, day)| inputs.day_dir(*year, *day))
+        .collect();
+    let args: Vec<_> = args.iter().filter(|a| *a != "--watch").cloned().collect();
+    watch::watch(&args, &sources, &data_dirs)
 }
 
 fn main() {
-    let args = std::env::args();
-    let start = SystemTime::now();
-    if args.len() == 1 {
-        run_solvers(&PROBLEMS.len());
-    } else {
-        let day_set: HashSet<usize> = args
-            .skip(1)
-            .flat_map(|s| get_arg_days(s.as_str()))
-            .collect();
-        let mut days: Vec<&usize> = day_set.iter().collect();
-        days.sort();
-        days.iter().for_each(|d| {
-            if *d != days[0] {
-                println!("");
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut iter = args.iter().cloned().peekable();
+    let new = iter.next_if(|arg| arg == "new").is_some();
+    let options = Options::parse(iter).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(2);
+    });
+    if options.mem {
+        mem::enable();
+    }
+    if new {
+        if let Err(err) = scaffold::new_day(&options) {
+            eprintln!("Error: {}", err);
+            std::process::exit(2);
+        }
+        return;
+    }
+    let start = Instant::now();
+    let mut baseline = (Baseline::default(), Baseline::default());
+    if let Some(path) = &options.baseline {
+        baseline.0 = Baseline::load(path).unwrap_or_else(|err| {
+            eprintln!("Error: {}", err);
+            std::process::exit(2);
+        });
+    }
+    let stdin = match options.stdin {
+        true => {
+            let mut text = String::new();
+            if let Err(err) = std::io::stdin().read_to_string(&mut text) {
+                eprintln!("Error: {}", err);
+                std::process::exit(2);
             }
-            run_solvers(*d)
+            Some(Arc::new(text))
+        }
+        false => None,
+    };
+    let latest = PROBLEMS.last().unwrap();
+    // Inputs are only downloaded when a session token is available
+    let upstream = std::env::var("ADVENT_SESSION")
+        .ok()
+        .filter(|session| !session.trim().is_empty())
+        .map(|session| {
+            Box::new(HttpProvider::new(&options.input_url(), &session)) as Box<dyn InputProvider>
         });
+    let inputs = FsCache::new(options.data_dir(), latest.year, upstream);
+    let latest_day = latest.latest_day().unwrap().to_string();
+    let selectors = match options.selectors.is_empty() {
+        true => vec![latest_day],
+        false => options.selectors.clone(),
+    };
+    let (tasks, unimplemented) = (selectors.iter())
+        .map(|s| Selector::parse(s, PROBLEMS))
+        .collect::<Result<Vec<_>, _>>()
+        .and_then(|selectors| plan_tasks(&selectors, &options, stdin))
+        .unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        });
+    if options.watch {
+        watch_tasks(&args, &tasks, &unimplemented, &options, &inputs);
+    }
+    let mut history = History::from_env();
+    options.format.begin();
+    options.format.unimplemented(&unimplemented);
+    let mut failures = 0;
+    // Results arrive in year/day/part order so a day is finished once the next
+    // starts
+    let mut current: Option<(DayKey, Answers, f64)> = None;
+    let cache = Arc::new(Cache::default());
+    let work = |task: &Task| run_task(task, &options, &inputs, &cache);
+    pool::run_ordered(&tasks, options.jobs, work, |mut part_result| {
+        let key = (part_result.year, part_result.day);
+        if current.as_ref().is_none_or(|(k, _, _)| *k != key) {
+            if let Some((_, _, day_ms)) = current.take() {
+                options.format.day_end(day_ms);
+            }
+            let year = Some(key.0).filter(|y| *y != latest.year);
+            let first = key == (tasks[0].year, tasks[0].day);
+            options.format.day_start(year, key.1, first);
+            current = Some((key, load_answers(key.0, key.1, &options, &inputs), 0_f64));
+        }
+        let ((year, day), answers, day_ms) = current.as_mut().unwrap();
+        *day_ms += part_result.elapsed_ms + part_result.parse_ms_once();
+        if let Some(stats) = &part_result.stats {
+            let (label, file) = (part_result.label(), &part_result.file);
+            part_result.change_pct = (baseline.0).change(*year, *day, &label, file, stats.median);
+            (baseline.1).insert(*year, *day, &label, file, stats.median);
+        }
+        if options.check {
+            let verdict = answers.check(
+                &part_result.file,
+                &part_result.label(),
+                part_result.result.as_ref().ok(),
+            );
+            if let Verdict::Fail(_) = verdict {
+                failures += 1;
+            }
+            part_result.verdict = Some(verdict);
+        }
+        if let Some(history) = &mut history {
+            history.update(&mut part_result);
+        }
+        options.format.part(&part_result);
+    });
+    if let Some(history) = &history {
+        history.save();
+    }
+    if let Some((_, _, day_ms)) = current {
+        options.format.day_end(day_ms);
+    }
+    options.format.end(ms_since(start), failures);
+    if let Some(path) = &options.save_baseline
+        && let Err(err) = baseline.1.save(path)
+    {
+        eprintln!("Error: {}", err);
+    }
+    if failures > 0 {
+        std::process::exit(1);
     }
-    println!("[{:0.2}ms]", ms_since(start));
 }
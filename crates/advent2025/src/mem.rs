@@ -0,0 +1,105 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+// Allocations made while running a single part
+#[derive(Clone, Copy)]
+pub struct MemStats {
+    pub peak_bytes: usize, // Most memory held at once beyond what was live before
+    pub total_bytes: usize,
+    pub allocations: usize,
+}
+
+const EMPTY: MemStats = MemStats {
+    peak_bytes: 0,
+    total_bytes: 0,
+    allocations: 0,
+};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+// Counters are kept per thread so parts running in parallel don't mix
+thread_local! {
+    static CURRENT: Cell<isize> = const { Cell::new(0) };
+    static STATS: Cell<MemStats> = const { Cell::new(EMPTY) };
+}
+
+fn record(change: isize, allocated: usize) {
+    // Counters may already be gone while a thread shuts down
+    let _ = CURRENT.try_with(|current| {
+        current.set(current.get() + change);
+        let _ = STATS.try_with(|stats| {
+            let mut s = stats.get();
+            s.peak_bytes = s.peak_bytes.max(current.get().max(0) as usize);
+            if allocated > 0 {
+                s.total_bytes += allocated;
+                s.allocations += 1;
+            }
+            stats.set(s);
+        });
+    });
+}
+
+// Passes everything through to the system allocator, counting along the way
+// once enabled by --mem
+pub struct Counting;
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() && ENABLED.load(Ordering::Relaxed) {
+            record(layout.size() as isize, layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        if ENABLED.load(Ordering::Relaxed) {
+            record(-(layout.size() as isize), 0);
+        }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = unsafe { System.realloc(ptr, layout, new_size) };
+        if !new_ptr.is_null() && ENABLED.load(Ordering::Relaxed) {
+            record(new_size as isize - layout.size() as isize, new_size);
+        }
+        new_ptr
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+// Runs f on this thread, counting its allocations if enabled
+pub fn measure<R>(f: impl FnOnce() -> R) -> (R, Option<MemStats>) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return (f(), None);
+    }
+    CURRENT.with(|c| c.set(0));
+    STATS.with(|s| s.set(EMPTY));
+    let result = f();
+    (result, Some(STATS.with(|s| s.get())))
+}
+
+// Byte counts in the largest binary unit which keeps them above one
+pub fn format_bytes(bytes: usize) -> String {
+    let units = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{} B", bytes),
+        _ => format!("{:0.1} {}", value, units[unit]),
+    }
+}
@@ -0,0 +1,172 @@
+use std::{env, path::PathBuf, time::Duration};
+
+use adventlib::log::Level;
+
+use crate::report::Format;
+
+pub struct Options {
+    pub check: bool,
+    pub format: Format,
+    pub bench: Option<usize>, // Number of timed runs per part
+    pub warmup: usize,
+    pub baseline: Option<PathBuf>,
+    pub save_baseline: Option<PathBuf>,
+    pub jobs: usize,
+    pub file: Option<PathBuf>, // Input fed to every selected part instead of data/<day>/
+    pub stdin: bool,
+    pub data_dir: Option<PathBuf>,
+    pub input_url: Option<String>,
+    pub watch: bool,
+    pub mem: bool,                     // Count allocations of every part
+    pub verbose: usize,                // Number of -v, showing messages of solvers
+    pub timeout: Option<Duration>,     // Time each run of a part gets before it's abandoned
+    pub params: Vec<(String, String)>, // Overrides for parts which take parameters
+    pub selectors: Vec<String>,
+}
+
+impl Options {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options {
+            check: false,
+            format: Format::Text,
+            bench: None,
+            warmup: 3,
+            baseline: None,
+            save_baseline: None,
+            jobs: 1,
+            file: None,
+            stdin: false,
+            data_dir: None,
+            input_url: None,
+            watch: false,
+            mem: false,
+            verbose: 0,
+            timeout: None,
+            params: vec![],
+            selectors: vec![],
+        };
+        while let Some(arg) = args.next() {
+            if arg == "-" {
+                options.stdin = true;
+                continue;
+            }
+            if arg == "-v" || arg == "-vv" {
+                options.verbose += arg.len() - 1;
+                continue;
+            }
+            if !arg.starts_with("--") {
+                options.selectors.push(arg);
+                continue;
+            }
+            // Options with values accept both "--name value" and "--name=value"
+            let (name, inline) = match arg.split_once('=') {
+                Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            let mut value = || {
+                inline
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("Missing value for {}", name))
+            };
+            match name.as_str() {
+                "--check" => options.check = true,
+                "--format" => options.format = value()?.parse()?,
+                "--bench" => options.bench = Some(parse_count(&name, &value()?)?),
+                "--warmup" => options.warmup = parse_count(&name, &value()?)?,
+                "--baseline" => options.baseline = Some(value()?.into()),
+                "--save-baseline" => options.save_baseline = Some(value()?.into()),
+                "--file" => options.file = Some(value()?.into()),
+                "--stdin" => options.stdin = true,
+                "--data-dir" => options.data_dir = Some(value()?.into()),
+                "--input-url" => options.input_url = Some(value()?),
+                "--watch" => options.watch = true,
+                "--verbose" => options.verbose += 1,
+                "--mem" => options.mem = true,
+                "--timeout" => options.timeout = Some(parse_seconds(&name, &value()?)?),
+                "--param" => options.params.push(parse_param(&value()?)?),
+                "--jobs" => options.jobs = parse_count(&name, &value()?)?,
+                _ => return Err(format!("Unknown option {}", name)),
+            }
+        }
+        if options.watch && options.stdin {
+            return Err("--watch can't re-read --stdin, use --file instead".to_string());
+        }
+        if options.stdin && options.file.is_some() {
+            return Err("--stdin and --file can't be used together".to_string());
+        }
+        if options.jobs == 0 {
+            options.jobs = std::thread::available_parallelism().map_or(1, |n| n.get());
+        }
+        if options.bench == Some(0) {
+            return Err("--bench needs at least one run".to_string());
+        }
+        if options.bench.is_none()
+            && (options.baseline.is_some() || options.save_baseline.is_some())
+        {
+            return Err("--baseline and --save-baseline require --bench".to_string());
+        }
+        Ok(options)
+    }
+}
+
+impl Options {
+    // Solvers are quiet unless asked, with -v showing warnings and progress and
+    // -vv adding debug output
+    pub fn log_level(&self) -> Option<Level> {
+        match self.verbose {
+            0 => None,
+            1 => Some(Level::Info),
+            _ => Some(Level::Debug),
+        }
+    }
+
+    // Puzzle data lives in --data-dir, then $ADVENT_DATA_DIR, then ./data and
+    // finally the data directory of the workspace the binary was built from so
+    // it can be launched from anywhere.
+    pub fn data_dir(&self) -> PathBuf {
+        if let Some(dir) = &self.data_dir {
+            return dir.clone();
+        }
+        if let Some(dir) = env::var_os("ADVENT_DATA_DIR") {
+            return dir.into();
+        }
+        let local = PathBuf::from("data");
+        if local.is_dir() {
+            return local;
+        }
+        let workspace = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/../../data"));
+        if workspace.is_dir() { workspace } else { local }
+    }
+
+    // Missing inputs are downloaded from --input-url, then $ADVENT_INPUT_URL,
+    // and otherwise from the puzzle site itself.
+    pub fn input_url(&self) -> String {
+        if let Some(url) = &self.input_url {
+            return url.clone();
+        }
+        env::var("ADVENT_INPUT_URL").unwrap_or("https://adventofcode.com".to_string())
+    }
+}
+
+fn parse_param(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(format!("Expected name=value for --param, got {}", value)),
+    }
+}
+
+fn parse_count(name: &str, value: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Expected a count for {}, got {}", name, value))
+}
+
+fn parse_seconds(name: &str, value: &str) -> Result<Duration, String> {
+    (value.parse::<f64>().ok())
+        .filter(|s| s.is_finite() && *s > 0_f64)
+        .map(Duration::from_secs_f64)
+        .ok_or_else(|| format!("Expected a number of seconds for {}, got {}", name, value))
+}
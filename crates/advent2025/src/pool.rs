@@ -0,0 +1,52 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Mutex, mpsc},
+    thread,
+};
+
+// Maps every item on a pool of worker threads, handing results back on the
+// calling thread in the original item order as soon as each becomes available.
+pub fn run_ordered<T, R, F, C>(items: &[T], jobs: usize, work: F, mut consume: C)
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+    C: FnMut(R),
+{
+    if jobs <= 1 || items.len() <= 1 {
+        items.iter().map(&work).for_each(consume);
+        return;
+    }
+    let queue = Mutex::new(items.iter().enumerate());
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..jobs.min(items.len()) {
+            let sender = sender.clone();
+            let (queue, work) = (&queue, &work);
+            scope.spawn(move || {
+                loop {
+                    let next = queue.lock().unwrap().next();
+                    match next {
+                        Some((i, item)) => {
+                            if sender.send((i, work(item))).is_err() {
+                                break;
+                            }
+                        }
+                        None => break,
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        let mut pending = BTreeMap::new();
+        let mut next = 0;
+        for (i, result) in receiver {
+            pending.insert(i, result);
+            while let Some(result) = pending.remove(&next) {
+                consume(result);
+                next += 1;
+            }
+        }
+    });
+}
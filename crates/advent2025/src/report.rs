@@ -0,0 +1,403 @@
+use std::{collections::BTreeSet, str::FromStr};
+
+use adventlib::{Answer, log::Record, params::Params};
+
+use crate::{
+    answers::Verdict,
+    bench::Stats,
+    mem::{MemStats, format_bytes},
+};
+
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("Unknown format {} (expected text, json or csv)", s)),
+        }
+    }
+}
+
+// Outcome of running a single part against its input file
+pub struct PartResult {
+    pub year: u32,
+    pub day: usize,
+    pub name: &'static str,
+    pub index: u32, // Disambiguates parts sharing a name, starting at 1
+    pub file: String,
+    pub params: Params,
+    pub result: Result<Answer, String>,
+    pub elapsed_ms: f64,
+    pub parse_ms: Option<f64>,       // Parsing time of two stage parts
+    pub parse_cached: bool,          // Whether an earlier part's parsed value was used
+    pub parse_mem: Option<MemStats>, // Allocations of the parse, unless cached
+    pub mem: Option<MemStats>,
+    pub logs: Vec<Record>, // Messages of the solver kept by -v or -vv
+    pub timed_out: bool,   // Abandoned after --timeout
+    pub verdict: Option<Verdict>,
+    pub stats: Option<Stats>,
+    pub change_pct: Option<f64>, // Median change relative to a benchmark baseline
+    pub previous: Option<String>, // Differing answer of the last run in watch mode
+}
+
+fn mem_text(m: &MemStats) -> String {
+    format!(
+        "peak {}, total {} in {} allocations",
+        format_bytes(m.peak_bytes),
+        format_bytes(m.total_bytes),
+        m.allocations
+    )
+}
+
+fn mem_json(mem: &Option<MemStats>) -> String {
+    match mem {
+        Some(m) => format!(
+            "{{\"peak_bytes\":{},\"total_bytes\":{},\"allocations\":{}}}",
+            m.peak_bytes, m.total_bytes, m.allocations
+        ),
+        None => "null".to_string(),
+    }
+}
+
+fn mem_csv(mem: &Option<MemStats>) -> String {
+    match mem {
+        Some(m) => format!("{},{},{}", m.peak_bytes, m.total_bytes, m.allocations),
+        None => ",,".to_string(),
+    }
+}
+
+impl PartResult {
+    pub fn label(&self) -> String {
+        format!("{} {}", self.name, self.index)
+    }
+
+    // Parsing time counted against this part, which is only the part which did
+    // the parsing so that totals include each parse once
+    pub fn parse_ms_once(&self) -> f64 {
+        match self.parse_cached {
+            true => 0_f64,
+            false => self.parse_ms.unwrap_or(0_f64),
+        }
+    }
+
+    fn parse_summary(&self) -> String {
+        match (self.parse_ms, self.parse_cached) {
+            (None, _) => String::new(),
+            (Some(_), true) => ", parse cached".to_string(),
+            (Some(ms), false) => match &self.parse_mem {
+                Some(m) => format!(", parse {:0.2}ms ({})", ms, mem_text(m)),
+                None => format!(", parse {:0.2}ms", ms),
+            },
+        }
+    }
+
+    fn mem_summary(&self) -> String {
+        match &self.mem {
+            Some(m) => format!(", {}", mem_text(m)),
+            None => String::new(),
+        }
+    }
+
+    fn label_with_params(&self) -> String {
+        match self.params.is_empty() {
+            true => self.label(),
+            false => format!("{} ({})", self.label(), self.params),
+        }
+    }
+
+    fn status(&self) -> &'static str {
+        if self.timed_out {
+            return "TIMEOUT";
+        }
+        match self.verdict {
+            Some(Verdict::Pass) => "PASS",
+            Some(Verdict::Fail(_)) => "FAIL",
+            Some(Verdict::Unknown) => "UNKNOWN",
+            None => "",
+        }
+    }
+}
+
+// Text output keeps the original human readable layout while json emits one
+// object per line and csv one row per part after a header.
+impl Format {
+    pub fn begin(&self) {
+        if let Format::Csv = self {
+            println!(
+                "year,day,part,index,file,answer,elapsed_ms,error,status,runs,min_ms,median_ms,mean_ms,stddev_ms,change_pct,params,parse_ms,parse_cached,peak_bytes,total_bytes,allocations,parse_peak_bytes,parse_total_bytes,parse_allocations,changed_from"
+            );
+        }
+    }
+
+    // Days are listed as plain numbers unless they come from more than one year
+    pub fn unimplemented(&self, days: &[(u32, usize)]) {
+        if days.is_empty() {
+            return;
+        }
+        match self {
+            Format::Text => {
+                let years = days.iter().map(|(y, _)| y).collect::<BTreeSet<_>>();
+                let days: Vec<_> = (days.iter())
+                    .map(|(year, day)| match years.len() {
+                        1 => day.to_string(),
+                        _ => format!("{}:{}", year, day),
+                    })
+                    .collect();
+                println!("Not implemented: day {}\n", days.join(", "));
+            }
+            Format::Json => days.iter().for_each(|(year, day)| {
+                println!(
+                    "{{\"year\":{},\"day\":{},\"part\":null,\"error\":\"Not implemented\"}}",
+                    year, day
+                )
+            }),
+            Format::Csv => (days.iter()).for_each(|(year, day)| {
+                println!("{},{},,,,,,Not implemented,,,,,,,,,,,,,,,,,", year, day)
+            }),
+        }
+    }
+
+    // The year is only shown for days outside the latest year
+    #[allow(clippy::println_empty_string)]
+    pub fn day_start(&self, year: Option<u32>, day: usize, first: bool) {
+        if let Format::Text = self {
+            if !first {
+                println!("");
+            }
+            match year {
+                Some(year) => println!("Day {} ({}):", day, year),
+                None => println!("Day {}:", day),
+            }
+        }
+    }
+
+    // Logged messages go to stderr ahead of the part so they neither mix with
+    // nor break the answers whatever the format
+    pub fn part(&self, part: &PartResult) {
+        part.logs.iter().for_each(|record| eprintln!("{}", record));
+        match self {
+            Format::Text => {
+                let status = match part.status() {
+                    "" => String::new(),
+                    s => format!("\t{}", s),
+                };
+                match &part.result {
+                    // Grids start on their own line so their rows line up
+                    Ok(Answer::Grid(rows)) => {
+                        println!(" > {}:{}", part.label_with_params(), status);
+                        rows.iter().for_each(|row| println!("     {}", row));
+                    }
+                    Ok(answer) => {
+                        println!(" > {}:\t{}{}", part.label_with_params(), answer, status)
+                    }
+                    Err(err) => {
+                        println!(" > {}:\tError: {}{}", part.label_with_params(), err, status)
+                    }
+                }
+                if let Some(Verdict::Fail(diff)) = &part.verdict {
+                    println!("{}", diff);
+                }
+                if let Some(previous) = &part.previous {
+                    println!("   CHANGED, was: {}", previous);
+                }
+                let change = match part.change_pct {
+                    Some(pct) => format!(" {:+0.1}% vs baseline", pct),
+                    None => String::new(),
+                };
+                match &part.stats {
+                    Some(s) => println!(
+                        "   [min {:0.3}ms, median {:0.3}ms, mean {:0.3}ms ± {:0.3}ms over {} runs{}{}]{}",
+                        s.min,
+                        s.median,
+                        s.mean,
+                        s.stddev,
+                        s.runs,
+                        part.parse_summary(),
+                        part.mem_summary(),
+                        change
+                    ),
+                    None => println!(
+                        "   [{:0.2}ms{}{}]",
+                        part.elapsed_ms,
+                        part.parse_summary(),
+                        part.mem_summary()
+                    ),
+                }
+            }
+            Format::Json => {
+                let (answer, error) = match &part.result {
+                    Ok(answer) => (json_answer(answer), "null".to_string()),
+                    Err(err) => ("null".to_string(), json_string(err)),
+                };
+                let status = match part.status() {
+                    "" => "null".to_string(),
+                    s => json_string(s),
+                };
+                let stats = match &part.stats {
+                    Some(s) => format!(
+                        "{{\"runs\":{},\"min_ms\":{:0.3},\"median_ms\":{:0.3},\"mean_ms\":{:0.3},\"stddev_ms\":{:0.3}}}",
+                        s.runs, s.min, s.median, s.mean, s.stddev
+                    ),
+                    None => "null".to_string(),
+                };
+                let change = match part.change_pct {
+                    Some(pct) => format!("{:0.2}", pct),
+                    None => "null".to_string(),
+                };
+                // The answer of the previous run under --watch when it differs
+                let changed_from = match &part.previous {
+                    Some(previous) => json_string(previous),
+                    None => "null".to_string(),
+                };
+                let params: Vec<_> = (part.params.iter())
+                    .map(|(name, value)| format!("{}:{}", json_string(name), json_string(value)))
+                    .collect();
+                println!(
+                    "{{\"year\":{},\"day\":{},\"part\":{},\"index\":{},\"file\":{},\"answer\":{},\"elapsed_ms\":{:0.3},\"error\":{},\"status\":{},\"bench\":{},\"change_pct\":{},\"params\":{{{}}},\"parse_ms\":{},\"parse_cached\":{},\"mem\":{},\"parse_mem\":{},\"changed_from\":{}}}",
+                    part.year,
+                    part.day,
+                    json_string(part.name),
+                    part.index,
+                    json_string(&part.file),
+                    answer,
+                    part.elapsed_ms,
+                    error,
+                    status,
+                    stats,
+                    change,
+                    params.join(","),
+                    part.parse_ms
+                        .map_or("null".to_string(), |ms| format!("{:0.3}", ms)),
+                    part.parse_cached,
+                    mem_json(&part.mem),
+                    mem_json(&part.parse_mem),
+                    changed_from,
+                );
+            }
+            Format::Csv => {
+                let (answer, error) = match &part.result {
+                    Ok(answer) => (answer.to_string(), ""),
+                    Err(err) => (String::new(), err.as_str()),
+                };
+                let stats = match &part.stats {
+                    Some(s) => format!(
+                        "{},{:0.3},{:0.3},{:0.3},{:0.3}",
+                        s.runs, s.min, s.median, s.mean, s.stddev
+                    ),
+                    None => ",,,,".to_string(),
+                };
+                println!(
+                    "{},{},{},{},{},{},{:0.3},{},{},{},{},{},{},{},{},{},{}",
+                    part.year,
+                    part.day,
+                    csv_field(part.name),
+                    part.index,
+                    csv_field(&part.file),
+                    csv_field(&answer),
+                    part.elapsed_ms,
+                    csv_field(error),
+                    part.status(),
+                    stats,
+                    part.change_pct
+                        .map_or(String::new(), |pct| format!("{:0.2}", pct)),
+                    csv_field(&part.params.to_string()),
+                    part.parse_ms
+                        .map_or(String::new(), |ms| format!("{:0.3}", ms)),
+                    part.parse_cached,
+                    mem_csv(&part.mem),
+                    mem_csv(&part.parse_mem),
+                    csv_field(part.previous.as_deref().unwrap_or_default()),
+                );
+            }
+        }
+    }
+
+    pub fn day_end(&self, elapsed_ms: f64) {
+        if let Format::Text = self {
+            println!("  [{:0.2}ms]", elapsed_ms);
+        }
+    }
+
+    pub fn end(&self, elapsed_ms: f64, failures: usize) {
+        if let Format::Text = self {
+            println!("[{:0.2}ms]", elapsed_ms);
+            if failures > 0 {
+                println!("{} part(s) failed", failures);
+            }
+        }
+    }
+}
+
+// Numbers are written as JSON numbers and grids as an array of rows
+fn json_answer(answer: &Answer) -> String {
+    match answer {
+        Answer::Int(_) | Answer::BigInt(_) => answer.to_string(),
+        Answer::Text(s) => json_string(s),
+        Answer::Grid(rows) => {
+            let rows: Vec<_> = rows.iter().map(|r| json_string(r)).collect();
+            format!("[{}]", rows.join(","))
+        }
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_strings_are_escaped() {
+        assert_eq!(json_string("plain"), r#""plain""#);
+        assert_eq!(json_string("a,b"), r#""a,b""#);
+        assert_eq!(json_string(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(json_string(r"back\slash"), r#""back\\slash""#);
+        assert_eq!(json_string("two\nlines\r\n"), r#""two\nlines\r\n""#);
+        assert_eq!(json_string("tab\there"), r#""tab\there""#);
+        assert_eq!(json_string("bell\u{7}nul\0"), r#""bell\u0007nul\u0000""#);
+        assert_eq!(json_string("été"), "\"été\"");
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_when_needed() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field(""), "");
+        assert_eq!(csv_field("tab\there"), "tab\there");
+        assert_eq!(csv_field("a,b"), r#""a,b""#);
+        assert_eq!(csv_field(r#"say "hi""#), r#""say ""hi""""#);
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field("cr\r"), "\"cr\r\"");
+    }
+}
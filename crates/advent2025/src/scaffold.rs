@@ -0,0 +1,93 @@
+use std::{
+    fs::{File, create_dir_all, write},
+    path::{Path, PathBuf},
+};
+
+use crate::options::Options;
+
+static TEMPLATE: &'static str = "use adventlib::{Part, Solution, all_parts, error::SolveError};
+
+pub static PARTS: &'static [Part<'static>] = &all_parts![part1, part2];
+
+fn part1(_input: &str) -> Solution {
+    Err(SolveError::unsupported(\"Not solved yet\"))
+}
+
+fn part2(_input: &str) -> Solution {
+    Err(SolveError::unsupported(\"Not solved yet\"))
+}
+";
+
+// The year of the src/dayN.rs modules, the same one setup_problems! infers
+fn crate_year() -> Option<u32> {
+    let name = env!("CARGO_PKG_NAME");
+    name.trim_start_matches(|c: char| !c.is_ascii_digit())
+        .parse()
+        .ok()
+}
+
+// Parses the [<year>:]<day> argument of `new`, defaulting to the crate's year
+fn parse_target(arg: &str) -> Result<(u32, usize), String> {
+    let (year, day) = match arg.split_once(':') {
+        Some((year, day)) => (
+            year.parse()
+                .map_err(|_| format!("Invalid year '{}'", year))?,
+            day,
+        ),
+        None => (
+            crate_year().ok_or("Can't tell which year this crate is for")?,
+            arg,
+        ),
+    };
+    match day.parse::<usize>() {
+        Ok(day) if (1..=25).contains(&day) => Ok((year, day)),
+        _ => Err(format!("Invalid day '{}' (expected 1 to 25)", day)),
+    }
+}
+
+// Where the module of a day lives, whether or not it exists yet
+pub fn module_path(year: u32, day: usize) -> PathBuf {
+    let mut src = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/src"));
+    if Some(year) != crate_year() {
+        src = src.join(format!("year{}", year));
+    }
+    src.join(format!("day{}.rs", day))
+}
+
+fn create_empty(path: &Path) -> Result<bool, String> {
+    if path.exists() {
+        return Ok(false);
+    }
+    File::create(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    Ok(true)
+}
+
+// Generates src/dayN.rs (or src/yearYYYY/dayN.rs for other years) along with
+// empty example and input files. setup_problems! picks the module up on the
+// next build so nothing else needs registering.
+pub fn new_day(options: &Options) -> Result<(), String> {
+    let [arg] = options.selectors.as_slice() else {
+        return Err("Usage: new [<year>:]<day>".to_string());
+    };
+    let (year, day) = parse_target(arg)?;
+    let module = module_path(year, day);
+    let src = module.parent().unwrap();
+    if module.exists() {
+        return Err(format!("{} already exists", module.display()));
+    }
+    let data = options
+        .data_dir()
+        .join(year.to_string())
+        .join(day.to_string());
+    create_dir_all(src).map_err(|err| format!("{}: {}", src.display(), err))?;
+    create_dir_all(&data).map_err(|err| format!("{}: {}", data.display(), err))?;
+    write(&module, TEMPLATE).map_err(|err| format!("{}: {}", module.display(), err))?;
+    println!("Created {}", module.display());
+    for file in ["example.txt", "input.txt"] {
+        let path = data.join(file);
+        if create_empty(&path)? {
+            println!("Created {}", path.display());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,208 @@
+use std::collections::BTreeSet;
+
+use adventlib::Year;
+
+static DELIMS: &'static [&'static str] = &["-", ".."];
+
+// A command line selector of the form [<year>:]<days>[:<parts>] where days is
+// a day, a range like 5-8 or 5..8 (either end may be left open), a comma
+// separated list of those, or .. for every day. Parts may be a name such as
+// Example, an index such as 2, or both as Input2. The year defaults to the
+// latest one and a lone year such as 2024 selects all of its days.
+pub struct Selector {
+    pub text: String,
+    pub year: u32,
+    pub days: BTreeSet<usize>,
+    name: Option<String>,
+    index: Option<u32>,
+}
+
+impl Selector {
+    pub fn parse(arg: &str, years: &[Year]) -> Result<Self, String> {
+        let arg = arg.trim();
+        let (year, rest) = match arg.split_once(':') {
+            Some((year, rest)) if is_year(year) => (Some(year), rest),
+            _ if is_year(arg) => (Some(arg), ".."),
+            _ => (None, arg),
+        };
+        let year = match year {
+            Some(year) => (years.iter())
+                .find(|y| y.year.to_string() == year)
+                .ok_or_else(|| format!("No puzzles for year {}", year))?,
+            None => years.last().ok_or("No puzzles registered")?,
+        };
+        let (days, parts) = match rest.split_once(':') {
+            Some((days, parts)) => (days, Some(parts.trim())),
+            None => (rest, None),
+        };
+        let mut selector = Selector {
+            text: arg.to_string(),
+            year: year.year,
+            days: get_arg_days(days, year.max_day())?,
+            name: None,
+            index: None,
+        };
+        if let Some(parts) = parts {
+            let split = parts.trim_end_matches(|c: char| c.is_ascii_digit());
+            let name = split.trim();
+            let index = &parts[split.len()..];
+            if name.is_empty() && index.is_empty() {
+                return Err(format!("Missing part after ':' in '{}'", arg));
+            }
+            selector.name = Some(name.to_string()).filter(|n| !n.is_empty());
+            selector.index = match index {
+                "" => None,
+                i => Some(
+                    i.parse()
+                        .map_err(|_| format!("Invalid part index in '{}'", arg))?,
+                ),
+            };
+        }
+        Ok(selector)
+    }
+
+    pub fn matches(&self, year: u32, day: usize, name: &str, index: u32) -> bool {
+        self.year == year
+            && self.days.contains(&day)
+            && self
+                .name
+                .as_ref()
+                .is_none_or(|n| n.eq_ignore_ascii_case(name))
+            && self.index.is_none_or(|i| i == index)
+    }
+}
+
+fn is_year(s: &str) -> bool {
+    s.len() == 4 && s.chars().all(|c| c.is_ascii_digit())
+}
+
+fn parse_day(s: &str, max_day: usize) -> Result<usize, String> {
+    match s.trim().parse::<usize>() {
+        Ok(day) if day > 0 && day <= max_day => Ok(day),
+        _ => Err(format!(
+            "Invalid day '{}' (expected 1 to {})",
+            s.trim(),
+            max_day
+        )),
+    }
+}
+
+fn get_arg_days(arg: &str, max_day: usize) -> Result<BTreeSet<usize>, String> {
+    let arg = arg.trim();
+    if arg == "-" {
+        return Err("A lone - reads stdin, use .. to select every day".to_string());
+    }
+    if arg == ".." {
+        return Ok((1..=max_day).collect());
+    }
+    if arg.contains(',') {
+        let mut days = BTreeSet::new();
+        for part in arg.split(',') {
+            days.extend(get_arg_days(part, max_day)?);
+        }
+        return Ok(days);
+    }
+    for delim in DELIMS {
+        if let Some((low, high)) = arg.split_once(delim) {
+            let low = match low.trim() {
+                "" => 1,
+                l => parse_day(l, max_day)?,
+            };
+            let high = match high.trim() {
+                "" => max_day,
+                h => parse_day(h, max_day)?,
+            };
+            if low > high {
+                return Err(format!("Empty day range '{}'", arg));
+            }
+            return Ok((low..=high).collect());
+        }
+    }
+    Ok(BTreeSet::from([parse_day(arg, max_day)?]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use adventlib::Part;
+
+    const NO_PARTS: &[Part] = &[];
+
+    static YEARS: &[Year] = &[
+        Year {
+            year: 2024,
+            days: &[],
+            parts: &[NO_PARTS; 25],
+        },
+        Year {
+            year: 2025,
+            days: &[],
+            parts: &[NO_PARTS; 12],
+        },
+    ];
+
+    fn days(arg: &str) -> Vec<usize> {
+        let selector = Selector::parse(arg, YEARS).unwrap();
+        selector.days.into_iter().collect()
+    }
+
+    #[test]
+    fn day_ranges() {
+        assert_eq!(days("3"), [3]);
+        assert_eq!(days("3-5"), [3, 4, 5]);
+        assert_eq!(days("3..5"), [3, 4, 5]);
+        assert_eq!(days("1,3-4, 12"), [1, 3, 4, 12]);
+        assert_eq!(days("-3"), [1, 2, 3]);
+        assert_eq!(days("10-"), [10, 11, 12]);
+        assert_eq!(days(".."), (1..=12).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn year_prefixes() {
+        let selector = Selector::parse("2024:20-", YEARS).unwrap();
+        assert_eq!(selector.year, 2024);
+        assert_eq!(
+            selector.days.into_iter().collect::<Vec<_>>(),
+            [20, 21, 22, 23, 24, 25]
+        );
+        let selector = Selector::parse("2024", YEARS).unwrap();
+        assert_eq!(selector.days.len(), 25);
+        assert_eq!(Selector::parse("5", YEARS).unwrap().year, 2025);
+        assert!(Selector::parse("2023:1", YEARS).is_err());
+    }
+
+    #[test]
+    fn parts_by_name_and_index() {
+        let selector = Selector::parse("4:example", YEARS).unwrap();
+        assert!(selector.matches(2025, 4, "Example", 1));
+        assert!(selector.matches(2025, 4, "Example", 2));
+        assert!(!selector.matches(2025, 4, "Input", 1));
+        assert!(!selector.matches(2025, 5, "Example", 1));
+        let selector = Selector::parse("4:2", YEARS).unwrap();
+        assert!(selector.matches(2025, 4, "Input", 2));
+        assert!(!selector.matches(2025, 4, "Input", 1));
+        let selector = Selector::parse("2024:4:Input2", YEARS).unwrap();
+        assert!(selector.matches(2024, 4, "Input", 2));
+        assert!(!selector.matches(2024, 4, "Example", 2));
+        assert!(!selector.matches(2025, 4, "Input", 2));
+    }
+
+    #[test]
+    fn rejected_selectors() {
+        let invalid = [
+            "0",
+            "13",
+            "2024:26",
+            "5-3",
+            "-",
+            "x",
+            "1-x",
+            "4:",
+            "4:Input99999999999",
+        ];
+        for arg in invalid {
+            assert!(Selector::parse(arg, YEARS).is_err(), "{}", arg);
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    env,
+    fs::{read_dir, read_to_string, remove_file, write},
+    path::{Path, PathBuf},
+    process::Command,
+    sync::atomic::{AtomicBool, Ordering},
+    thread::sleep,
+    time::{Duration, SystemTime},
+};
+
+use crate::report::PartResult;
+
+// Where the watcher tells each run to find the answers of the previous one
+static HISTORY_VAR: &'static str = "ADVENT_WATCH_HISTORY";
+
+// Set on Ctrl-C so the watcher can clean up before exiting
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(unix)]
+fn catch_interrupts() {
+    unsafe extern "C" {
+        fn signal(signum: i32, handler: extern "C" fn(i32)) -> usize;
+    }
+    extern "C" fn interrupted(_: i32) {
+        INTERRUPTED.store(true, Ordering::SeqCst);
+    }
+    // SIGINT and SIGTERM, which have the same numbers on every unix
+    for signum in [2, 15] {
+        unsafe { signal(signum, interrupted) };
+    }
+}
+
+// Elsewhere Ctrl-C ends the watcher at once and the history is left behind
+#[cfg(not(unix))]
+fn catch_interrupts() {}
+
+type Stamps = BTreeMap<PathBuf, Option<SystemTime>>;
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    path.metadata().and_then(|m| m.modified()).ok()
+}
+
+// Modification times of the watched files along with everything currently in
+// the watched directories, so new data files are noticed too.
+fn snapshot(files: &[PathBuf], dirs: &[PathBuf]) -> Stamps {
+    let mut stamps: Stamps = files.iter().map(|f| (f.clone(), modified(f))).collect();
+    for dir in dirs {
+        for entry in read_dir(dir).into_iter().flatten().flatten() {
+            stamps.insert(entry.path(), modified(&entry.path()));
+        }
+    }
+    stamps
+}
+
+fn rebuild() -> bool {
+    let cargo = env::var("CARGO").unwrap_or("cargo".to_string());
+    let manifest = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+    let mut command = Command::new(cargo);
+    command.args(["build", "--manifest-path", manifest]);
+    if !cfg!(debug_assertions) {
+        command.arg("--release");
+    }
+    command.status().is_ok_and(|status| status.success())
+}
+
+// Runs the binary again with the given arguments whenever a watched file
+// changes, rebuilding it first if one of the sources changed. Sources are
+// polled rather than watched so nothing platform specific is needed. The
+// answers of the last run are kept in a temporary file removed on Ctrl-C.
+pub fn watch(args: &[String], sources: &[PathBuf], data_dirs: &[PathBuf]) -> ! {
+    let exe = env::current_exe().unwrap_or_else(|err| {
+        eprintln!("Error: {}", err);
+        std::process::exit(2);
+    });
+    let history = env::temp_dir().join(format!("advent-watch-{}.tsv", std::process::id()));
+    let stop = || {
+        if INTERRUPTED.load(Ordering::SeqCst) {
+            let _ = remove_file(&history);
+            std::process::exit(130);
+        }
+    };
+    catch_interrupts();
+    let mut stamps = snapshot(sources, data_dirs);
+    let mut run = true;
+    loop {
+        if run {
+            let _ = Command::new(&exe)
+                .args(args)
+                .env(HISTORY_VAR, &history)
+                .status();
+            stop();
+            println!("\nWatching for changes (Ctrl-C to stop)");
+        }
+        sleep(Duration::from_millis(500));
+        stop();
+        let latest = snapshot(sources, data_dirs);
+        if latest == stamps {
+            run = false;
+            continue;
+        }
+        let source_changed = sources.iter().any(|s| latest.get(s) != stamps.get(s));
+        stamps = latest;
+        run = !source_changed || rebuild();
+        if !run {
+            println!("\nBuild failed, waiting for further changes");
+        }
+    }
+}
+
+fn escape(s: &str) -> String {
+    (s.replace('\\', "\\\\"))
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some(c) => out.push(c),
+            None => {}
+        }
+    }
+    out
+}
+
+// Answers of the previous run under the watcher keyed by year, day, part label
+// and input file, with errors stored as their message.
+pub struct History {
+    path: PathBuf,
+    answers: HashMap<(u32, usize, String, String), String>,
+}
+
+impl History {
+    // Only runs started by the watcher keep a history
+    pub fn from_env() -> Option<Self> {
+        let path = PathBuf::from(env::var_os(HISTORY_VAR)?);
+        let content = read_to_string(&path).unwrap_or_default();
+        let mut answers = HashMap::new();
+        for line in content.lines() {
+            let fields: Vec<_> = line.split('\t').collect();
+            if let [year, day, label, file, answer] = fields.as_slice()
+                && let (Ok(year), Ok(day)) = (year.parse(), day.parse())
+            {
+                let key = (year, day, unescape(label), unescape(file));
+                answers.insert(key, unescape(answer));
+            }
+        }
+        Some(History {
+            path: path,
+            answers: answers,
+        })
+    }
+
+    // Records the answer of a part, noting the previous one if it differs
+    pub fn update(&mut self, part: &mut PartResult) {
+        let answer = match &part.result {
+            Ok(answer) => answer.to_string(),
+            Err(err) => format!("Error: {}", err),
+        };
+        let key = (part.year, part.day, part.label(), part.file.clone());
+        if let Some(previous) = self.answers.insert(key, answer.clone())
+            && previous != answer
+        {
+            part.previous = Some(previous);
+        }
+    }
+
+    pub fn save(&self) {
+        let mut entries: Vec<_> = self.answers.iter().collect();
+        entries.sort();
+        let content: String = entries
+            .iter()
+            .map(|((year, day, label, file), answer)| {
+                format!(
+                    "{year}\t{day}\t{}\t{}\t{}\n",
+                    escape(label),
+                    escape(file),
+                    escape(answer)
+                )
+            })
+            .collect();
+        if let Err(err) = write(&self.path, content) {
+            eprintln!("Error: {}: {}", self.path.display(), err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escaped_answers_round_trip() {
+        let answers = [
+            "42",
+            "tab\tseparated",
+            "two\nlines",
+            "\t\n\t",
+            r"back\slash",
+            r"\t is not a tab",
+            r"trailing\",
+            "",
+        ];
+        for answer in answers {
+            let escaped = escape(answer);
+            assert!(!escaped.contains(['\t', '\n']), "{:?}", escaped);
+            assert_eq!(unescape(&escaped), answer);
+            // Each field of a history line keeps to its own column
+            let line = format!("{}\t{}", escape(answer), escape(answer));
+            let fields: Vec<_> = line.split('\t').map(unescape).collect();
+            assert_eq!(fields, [answer, answer]);
+        }
+    }
+}
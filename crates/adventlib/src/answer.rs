@@ -0,0 +1,127 @@
+use std::fmt;
+
+use crate::{
+    bigint::BigInt,
+    fraction::{Fraction, Integer},
+};
+
+// What a solver produces. Integers are kept as numbers so answers can be
+// compared numerically and serialized as such, with anything too large for an
+// i64 falling back to the big integer variant. Grids are multi-line pictures
+// such as letters drawn by the puzzle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Int(i64),
+    BigInt(BigInt),
+    Text(String),
+    Grid(Vec<String>),
+}
+
+impl Answer {
+    pub fn grid<T: ToString>(rows: impl IntoIterator<Item = T>) -> Self {
+        Answer::Grid(rows.into_iter().map(|r| r.to_string()).collect())
+    }
+
+    pub fn as_i128(&self) -> Option<i128> {
+        match self {
+            Answer::Int(i) => Some(*i as i128),
+            Answer::BigInt(i) => i.to_i128(),
+            _ => None,
+        }
+    }
+
+    pub fn as_bigint(&self) -> Option<BigInt> {
+        match self {
+            Answer::Int(i) => Some(BigInt::from(*i)),
+            Answer::BigInt(i) => Some(i.clone()),
+            _ => None,
+        }
+    }
+
+    // Whether this is the answer written down as expected, comparing numbers by
+    // value and everything else by its text
+    pub fn matches(&self, expected: &str) -> bool {
+        match self.as_bigint() {
+            Some(i) => expected.trim().parse::<BigInt>() == Ok(i),
+            None => self.to_string() == expected,
+        }
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Int(i) => write!(f, "{}", i),
+            Answer::BigInt(i) => write!(f, "{}", i),
+            Answer::Text(s) => write!(f, "{}", s),
+            Answer::Grid(rows) => write!(f, "{}", rows.join("\n")),
+        }
+    }
+}
+
+macro_rules! from_small_int {
+    ( $( $t:ty ),* ) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(value: $t) -> Self {
+                    Answer::Int(value as i64)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! from_large_int {
+    ( $( $t:ty ),* ) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(value: $t) -> Self {
+                    match i64::try_from(value) {
+                        Ok(i) => Answer::Int(i),
+                        Err(_) => Answer::BigInt(BigInt::from(value)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+from_small_int!(i8, i16, i32, i64, u8, u16, u32);
+from_large_int!(u64, usize, isize, i128, u128);
+
+impl From<BigInt> for Answer {
+    fn from(value: BigInt) -> Self {
+        match value.to_i128().and_then(|i| i64::try_from(i).ok()) {
+            Some(i) => Answer::Int(i),
+            None => Answer::BigInt(value),
+        }
+    }
+}
+
+// Whole fractions are numbers like any other integer, the rest are n/d
+impl<T: Integer + Into<Answer>> From<Fraction<T>> for Answer {
+    fn from(value: Fraction<T>) -> Self {
+        match value.is_integer() {
+            true => value.numerator().clone().into(),
+            false => Answer::Text(value.to_string()),
+        }
+    }
+}
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Answer::Text(value)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(value: &str) -> Self {
+        Answer::Text(value.to_string())
+    }
+}
+
+impl From<Vec<String>> for Answer {
+    fn from(value: Vec<String>) -> Self {
+        Answer::Grid(value)
+    }
+}
@@ -0,0 +1,633 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    ops::{Add, Div, Mul, Neg, Rem, Sub},
+    str::FromStr,
+};
+
+use crate::fraction::{Fraction, Integer};
+
+// Fractions which can't overflow, at the cost of allocating
+pub type BigRational = Fraction<BigInt>;
+
+// An integer of any size as a sign and magnitude, with the magnitude stored in
+// base 2^32 from the least significant digit. Magnitudes never end in a zero
+// digit and zero is never negative, so equal values have equal fields.
+#[derive(Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigInt {
+    negative: bool,
+    digits: Vec<u32>,
+}
+
+const BASE: u64 = 1 << 32;
+const DECIMAL_CHUNK: u32 = 1_000_000_000; // Largest power of ten in a digit
+
+fn trim(mut digits: Vec<u32>) -> Vec<u32> {
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+    digits
+}
+
+fn cmp_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut sum = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0_u64;
+    for i in 0..a.len().max(b.len()) {
+        let total = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        sum.push(total as u32);
+        carry = total >> 32;
+    }
+    sum.push(carry as u32);
+    trim(sum)
+}
+
+// Requires a >= b
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0_i64;
+    for (i, x) in a.iter().enumerate() {
+        let mut total = *x as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = (total < 0) as i64;
+        if total < 0 {
+            total += BASE as i64;
+        }
+        difference.push(total as u32);
+    }
+    trim(difference)
+}
+
+fn mul_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut product = vec![0_u32; a.len() + b.len()];
+    for (i, x) in a.iter().enumerate() {
+        let mut carry = 0_u64;
+        for (j, y) in b.iter().enumerate() {
+            let total = *x as u64 * *y as u64 + product[i + j] as u64 + carry;
+            product[i + j] = total as u32;
+            carry = total >> 32;
+        }
+        product[i + b.len()] = carry as u32;
+    }
+    trim(product)
+}
+
+fn divmod_small(a: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0_u32; a.len()];
+    let mut remainder = 0_u64;
+    for i in (0..a.len()).rev() {
+        let current = (remainder << 32) + a[i] as u64;
+        quotient[i] = (current / divisor as u64) as u32;
+        remainder = current % divisor as u64;
+    }
+    (trim(quotient), remainder as u32)
+}
+
+// Long division a bit at a time, which is plenty for puzzle sized numbers
+fn divmod_magnitude(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if b.len() == 1 {
+        let (quotient, remainder) = divmod_small(a, b[0]);
+        return (quotient, trim(vec![remainder]));
+    }
+    if cmp_magnitude(a, b) == Ordering::Less {
+        return (vec![], a.to_vec());
+    }
+    let mut quotient = vec![0_u32; a.len()];
+    let mut remainder: Vec<u32> = vec![];
+    for bit in (0..a.len() * 32).rev() {
+        remainder = add_magnitude(&remainder, &remainder);
+        if (a[bit / 32] >> (bit % 32)) & 1 == 1 {
+            remainder = add_magnitude(&remainder, &[1]);
+        }
+        if cmp_magnitude(&remainder, b) != Ordering::Less {
+            remainder = sub_magnitude(&remainder, b);
+            quotient[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+    (trim(quotient), remainder)
+}
+
+impl BigInt {
+    fn new(negative: bool, digits: Vec<u32>) -> Self {
+        let digits = trim(digits);
+        BigInt {
+            negative: negative && !digits.is_empty(),
+            digits: digits,
+        }
+    }
+
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn signum(&self) -> i32 {
+        match (self.is_zero(), self.negative) {
+            (true, _) => 0,
+            (false, true) => -1,
+            (false, false) => 1,
+        }
+    }
+
+    pub fn abs(&self) -> Self {
+        BigInt::new(false, self.digits.clone())
+    }
+
+    // Quotient rounded towards zero and a remainder taking the sign of self,
+    // matching Rust's integer division, or None when dividing by zero
+    pub fn divmod(&self, divisor: &Self) -> Option<(Self, Self)> {
+        if divisor.is_zero() {
+            return None;
+        }
+        let (quotient, remainder) = divmod_magnitude(&self.digits, &divisor.digits);
+        Some((
+            BigInt::new(self.negative != divisor.negative, quotient),
+            BigInt::new(self.negative, remainder),
+        ))
+    }
+
+    // Never negative, and zero only if both are
+    pub fn gcd(&self, other: &Self) -> Self {
+        let (mut a, mut b) = (self.digits.clone(), other.digits.clone());
+        while !b.is_empty() {
+            let (_, remainder) = divmod_magnitude(&a, &b);
+            (a, b) = (b, remainder);
+        }
+        BigInt::new(false, a)
+    }
+
+    pub fn pow(&self, mut exponent: u32) -> Self {
+        let (mut base, mut result) = (self.clone(), BigInt::from(1));
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &result * &base;
+            }
+            base = &base * &base;
+            exponent >>= 1;
+        }
+        result
+    }
+
+    pub fn to_i128(&self) -> Option<i128> {
+        if self.digits.len() > 4 {
+            return None;
+        }
+        let magnitude = (self.digits.iter().rev()).fold(0_u128, |m, d| (m << 32) | *d as u128);
+        match self.negative {
+            true => 0_i128.checked_sub_unsigned(magnitude),
+            false => i128::try_from(magnitude).ok(),
+        }
+    }
+
+    fn from_u128(negative: bool, mut magnitude: u128) -> Self {
+        let mut digits = vec![];
+        while magnitude > 0 {
+            digits.push(magnitude as u32);
+            magnitude >>= 32;
+        }
+        BigInt::new(negative, digits)
+    }
+
+    fn add_signed(&self, rhs: &Self, rhs_negative: bool) -> Self {
+        if self.negative == rhs_negative {
+            return BigInt::new(self.negative, add_magnitude(&self.digits, &rhs.digits));
+        }
+        match cmp_magnitude(&self.digits, &rhs.digits) {
+            Ordering::Less => BigInt::new(rhs_negative, sub_magnitude(&rhs.digits, &self.digits)),
+            _ => BigInt::new(self.negative, sub_magnitude(&self.digits, &rhs.digits)),
+        }
+    }
+}
+
+macro_rules! from_signed {
+    ( $( $t:ty ),* ) => {
+        $(
+            impl From<$t> for BigInt {
+                fn from(value: $t) -> Self {
+                    BigInt::from_u128(value < 0, value.unsigned_abs() as u128)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! from_unsigned {
+    ( $( $t:ty ),* ) => {
+        $(
+            impl From<$t> for BigInt {
+                fn from(value: $t) -> Self {
+                    BigInt::from_u128(false, value as u128)
+                }
+            }
+        )*
+    };
+}
+
+from_signed!(i8, i16, i32, i64, i128, isize);
+from_unsigned!(u8, u16, u32, u64, u128, usize);
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitude(&self.digits, &other.digits),
+            (true, true) => cmp_magnitude(&other.digits, &self.digits),
+        }
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        let mut chunks = vec![];
+        let mut digits = self.digits.clone();
+        while !digits.is_empty() {
+            let (quotient, chunk) = divmod_small(&digits, DECIMAL_CHUNK);
+            chunks.push(chunk);
+            digits = quotient;
+        }
+        let mut text = chunks.pop().unwrap().to_string();
+        chunks
+            .iter()
+            .rev()
+            .for_each(|c| text.push_str(&format!("{:09}", c)));
+        f.pad_integral(!self.negative, "", &text)
+    }
+}
+
+impl fmt::Debug for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseBigIntError;
+
+impl fmt::Display for ParseBigIntError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid big integer")
+    }
+}
+
+impl std::error::Error for ParseBigIntError {}
+
+// Decimal digits with an optional sign, read nine digits at a time
+impl FromStr for BigInt {
+    type Err = ParseBigIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParseBigIntError);
+        }
+        let mut magnitude = vec![];
+        let first = digits.len() % 9;
+        let chunks = [&digits[..first]].into_iter().chain(
+            (first..digits.len())
+                .step_by(9)
+                .map(|i| &digits[i..(i + 9)]),
+        );
+        for chunk in chunks.filter(|c| !c.is_empty()) {
+            let scale = 10_u32.pow(chunk.len() as u32);
+            magnitude = mul_magnitude(&magnitude, &[scale]);
+            magnitude = add_magnitude(&magnitude, &[chunk.parse().unwrap()]);
+        }
+        Ok(BigInt::new(negative, magnitude))
+    }
+}
+
+impl Neg for BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> Self::Output {
+        BigInt::new(!self.negative, self.digits)
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> Self::Output {
+        BigInt::new(!self.negative, self.digits.clone())
+    }
+}
+
+macro_rules! bigint_ops {
+    ($lhs:ty, $rhs:ty) => {
+        impl Add<$rhs> for $lhs {
+            type Output = BigInt;
+
+            fn add(self, rhs: $rhs) -> Self::Output {
+                self.add_signed(&rhs, rhs.negative)
+            }
+        }
+
+        impl Sub<$rhs> for $lhs {
+            type Output = BigInt;
+
+            fn sub(self, rhs: $rhs) -> Self::Output {
+                self.add_signed(&rhs, !rhs.negative)
+            }
+        }
+
+        impl Mul<$rhs> for $lhs {
+            type Output = BigInt;
+
+            fn mul(self, rhs: $rhs) -> Self::Output {
+                let digits = mul_magnitude(&self.digits, &rhs.digits);
+                BigInt::new(self.negative != rhs.negative, digits)
+            }
+        }
+
+        // Division and remainder panic on a zero divisor like the primitives
+        impl Div<$rhs> for $lhs {
+            type Output = BigInt;
+
+            fn div(self, rhs: $rhs) -> Self::Output {
+                self.divmod(&rhs).expect("attempt to divide by zero").0
+            }
+        }
+
+        impl Rem<$rhs> for $lhs {
+            type Output = BigInt;
+
+            fn rem(self, rhs: $rhs) -> Self::Output {
+                (self.divmod(&rhs))
+                    .expect("attempt to calculate the remainder with a divisor of zero")
+                    .1
+            }
+        }
+    };
+}
+
+bigint_ops!(BigInt, BigInt);
+bigint_ops!(&BigInt, BigInt);
+bigint_ops!(BigInt, &BigInt);
+bigint_ops!(&BigInt, &BigInt);
+
+impl std::iter::Sum for BigInt {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(BigInt::zero(), |a, b| a + b)
+    }
+}
+
+impl std::iter::Product for BigInt {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(BigInt::from(1), |a, b| a * b)
+    }
+}
+
+// Only dividing by zero can fail
+impl Integer for BigInt {
+    fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        Some(self + rhs)
+    }
+
+    fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        Some(self - rhs)
+    }
+
+    fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        Some(self * rhs)
+    }
+
+    fn checked_div(&self, rhs: &Self) -> Option<Self> {
+        self.divmod(rhs).map(|(quotient, _)| quotient)
+    }
+
+    fn checked_rem(&self, rhs: &Self) -> Option<Self> {
+        self.divmod(rhs).map(|(_, remainder)| remainder)
+    }
+
+    fn checked_neg(&self) -> Option<Self> {
+        Some(-self)
+    }
+
+    fn gcd(&self, other: &Self) -> Option<Self> {
+        Some(BigInt::gcd(self, other))
+    }
+
+    fn to_f64(&self) -> f64 {
+        let magnitude = (self.digits.iter().rev()).fold(0_f64, |m, d| m * BASE as f64 + *d as f64);
+        if self.negative { -magnitude } else { magnitude }
+    }
+}
+
+// Checks every operation against i128 on random values small enough for the
+// result to fit, using a fixed seed so failures reproduce
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ilp::{Bound, LinearEquation, LinearSystem, ReducedRowEcheleon};
+
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        // A value of up to the given number of bits with either sign
+        fn int(&mut self, max_bits: u32) -> i128 {
+            let bits = (self.next() % (max_bits as u64 + 1)) as u32;
+            let raw = ((self.next() as u128) << 64) | self.next() as u128;
+            let magnitude = match bits {
+                0 => 0,
+                _ => (raw >> (128 - bits)) as i128,
+            };
+            if self.next() & 1 == 1 {
+                -magnitude
+            } else {
+                magnitude
+            }
+        }
+    }
+
+    fn check(max_bits: u32, test: impl Fn(i128, i128)) {
+        let mut rng = Rng(0x2545f4914f6cdd1d);
+        for _ in 0..2000 {
+            let (a, b) = (rng.int(max_bits), rng.int(max_bits));
+            test(a, b);
+        }
+    }
+
+    fn big(i: i128) -> BigInt {
+        BigInt::from(i)
+    }
+
+    #[test]
+    fn add_and_sub_match_i128() {
+        check(125, |a, b| {
+            assert_eq!(big(a) + big(b), big(a + b), "{a} + {b}");
+            assert_eq!(big(a) - big(b), big(a - b), "{a} - {b}");
+            assert_eq!(-big(a), big(-a));
+        });
+    }
+
+    #[test]
+    fn mul_matches_i128() {
+        check(63, |a, b| {
+            assert_eq!(big(a) * big(b), big(a * b), "{a} * {b}")
+        });
+    }
+
+    #[test]
+    fn divmod_matches_i128() {
+        check(126, |a, b| {
+            if b != 0 {
+                assert_eq!(
+                    big(a).divmod(&big(b)),
+                    Some((big(a / b), big(a % b))),
+                    "{a} / {b}"
+                );
+            }
+        });
+        check(126, |a, b| {
+            let b = b >> 64;
+            if b != 0 {
+                assert_eq!(
+                    big(a).divmod(&big(b)),
+                    Some((big(a / b), big(a % b))),
+                    "{a} / {b}"
+                );
+            }
+        });
+        assert_eq!(big(1).divmod(&BigInt::zero()), None);
+    }
+
+    #[test]
+    fn gcd_matches_i128() {
+        let gcd = |mut a: i128, mut b: i128| {
+            while b != 0 {
+                (a, b) = (b, a % b);
+            }
+            a.abs()
+        };
+        check(126, |a, b| {
+            assert_eq!(big(a).gcd(&big(b)), big(gcd(a, b)), "gcd({a}, {b})")
+        });
+        check(40, |a, b| {
+            let c = a >> 8;
+            assert_eq!(big(a * c).gcd(&big(b * c)), big(gcd(a * c, b * c)));
+        });
+    }
+
+    #[test]
+    fn pow_matches_i128() {
+        check(12, |a, b| {
+            let exponent = (b.unsigned_abs() % 10) as u32;
+            assert_eq!(big(a).pow(exponent), big(a.pow(exponent)), "{a}^{exponent}");
+        });
+    }
+
+    #[test]
+    fn display_and_parse_match_i128() {
+        check(127, |a, _| {
+            assert_eq!(big(a).to_string(), a.to_string());
+            assert_eq!(a.to_string().parse::<BigInt>(), Ok(big(a)));
+            assert_eq!(big(a).to_i128(), Some(a));
+        });
+        for i in [i128::MIN, i128::MAX, 0, 1_000_000_000, -999_999_999] {
+            assert_eq!(big(i).to_string().parse::<BigInt>(), Ok(big(i)));
+            assert_eq!(big(i).to_i128(), Some(i));
+        }
+        assert_eq!("+12".parse::<BigInt>(), Ok(big(12)));
+        assert_eq!("-0".parse::<BigInt>(), Ok(BigInt::zero()));
+        for bad in ["", "-", "1-2", "12a", " 1"] {
+            assert_eq!(bad.parse::<BigInt>(), Err(ParseBigIntError));
+        }
+        assert_eq!(format!("{:>5}", big(-42)), "  -42");
+    }
+
+    #[test]
+    fn ordering_matches_i128() {
+        check(127, |a, b| {
+            assert_eq!(big(a).cmp(&big(b)), a.cmp(&b), "{a} <=> {b}")
+        });
+    }
+
+    #[test]
+    fn exceeds_i128() {
+        assert_eq!((big(i128::MAX) + big(1)).to_i128(), None);
+        assert_eq!((big(i128::MIN) - big(1)).to_i128(), None);
+        let two_128 = big(2).pow(128);
+        assert_eq!(
+            two_128.to_string(),
+            "340282366920938463463374607431768211456"
+        );
+        assert_eq!(&two_128 * &two_128 / &two_128, two_128);
+        assert_eq!((&two_128 * &two_128 + big(5)) % &two_128, big(5));
+        assert_eq!(two_128.to_string().parse::<BigInt>(), Ok(two_128.clone()));
+        assert_eq!(-(&two_128 - big(1)), big(1) - &two_128);
+    }
+
+    #[test]
+    fn rationals_solve_systems_like_fixed_width_fractions() {
+        // Buttons (3) (1,3) (2) (2,3) (0,2) (0,1) with joltages {3,5,4,7}
+        fn solutions<T: Integer>() -> Vec<Vec<T>> {
+            let buttons = [
+                vec![3],
+                vec![1, 3],
+                vec![2],
+                vec![2, 3],
+                vec![0, 2],
+                vec![0, 1],
+            ];
+            let joltages = [3, 5, 4, 7];
+            let system: LinearSystem<T> = (joltages.iter().enumerate())
+                .map(|(i, j)| LinearEquation {
+                    a: (buttons.iter())
+                        .map(|b| Fraction::from(T::from(b.contains(&i) as i32)))
+                        .collect(),
+                    b: Fraction::from(T::from(*j)),
+                })
+                .collect();
+            let mut rre = ReducedRowEcheleon::try_from(system).unwrap();
+            for i in 0..rre.get_var_count() {
+                rre.restrict_bound(i, Bound::closed_low(Fraction::zero()))
+                    .unwrap();
+            }
+            rre.infer_bounds().unwrap();
+            let mut solutions: Vec<_> = (rre.get_solutions()).collect::<Result<_, _>>().unwrap();
+            solutions.sort();
+            solutions
+        }
+        let expected: Vec<Vec<BigInt>> = (solutions::<i64>().into_iter())
+            .map(|s| s.into_iter().map(BigInt::from).collect())
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(solutions::<BigInt>(), expected);
+        let half = BigRational::checked_new(big(1), big(2)).unwrap();
+        assert_eq!(
+            crate::Answer::from(half),
+            crate::Answer::Text("1/2".to_string())
+        );
+    }
+}
@@ -0,0 +1,55 @@
+use std::{
+    cell::RefCell,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use crate::error::SolveError;
+
+// Set by the runner once a part has run out of time. Solvers with long loops
+// can poll the token of the thread they run on and give up early, as whatever
+// they return afterwards is thrown away.
+#[derive(Clone, Default)]
+pub struct Cancel(Arc<AtomicBool>);
+
+thread_local! {
+    static CURRENT: RefCell<Option<Cancel>> = const { RefCell::new(None) };
+}
+
+impl Cancel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // The token of the part running on this thread, which is never cancelled
+    // when running outside the runner such as in tests
+    pub fn current() -> Self {
+        CURRENT.with(|current| current.borrow().clone().unwrap_or_default())
+    }
+
+    // Makes this the current token while running the given function
+    pub fn scope<R>(&self, f: impl FnOnce() -> R) -> R {
+        let previous = CURRENT.with(|current| current.replace(Some(self.clone())));
+        let result = f();
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+        result
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    // Lets solvers bail out with `?`
+    pub fn check(&self) -> Result<(), SolveError> {
+        match self.is_cancelled() {
+            true => Err(SolveError::Cancelled),
+            false => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use std::fmt;
+
+// Failure modes shared by every solver. Parse errors remember where the bad
+// fragment lives in memory so the runner can later turn that into a line and
+// column within the input it handed to the solver.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    Parse(ParseError),
+    NoSolution(String),
+    Unsupported(String),
+    Cancelled, // Gave up after the runner's timeout
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    pub position: Option<(usize, usize)>, // 1-based line and column once located
+    origin: usize,
+}
+
+impl SolveError {
+    pub fn parse(fragment: &str, message: impl Into<String>) -> Self {
+        SolveError::Parse(ParseError {
+            message: message.into(),
+            position: None,
+            origin: fragment.as_ptr() as usize,
+        })
+    }
+
+    pub fn no_solution(message: impl Into<String>) -> Self {
+        SolveError::NoSolution(message.into())
+    }
+
+    pub fn unsupported(message: impl Into<String>) -> Self {
+        SolveError::Unsupported(message.into())
+    }
+
+    // Fills in the line and column of a parse error if its fragment was sliced
+    // out of the given input. Errors which are already located are left alone.
+    pub fn locate(self, input: &str) -> Self {
+        match self {
+            SolveError::Parse(mut err) if err.position.is_none() => {
+                let start = input.as_ptr() as usize;
+                if err.origin >= start && err.origin <= start + input.len() {
+                    let before = &input[..(err.origin - start)];
+                    let line = before.matches('\n').count() + 1;
+                    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+                    err.position = Some((line, column));
+                }
+                SolveError::Parse(err)
+            }
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::Parse(err) => match err.position {
+                Some((line, column)) => {
+                    write!(f, "Parse error at {line}:{column}: {}", err.message)
+                }
+                None => write!(f, "Parse error: {}", err.message),
+            },
+            SolveError::NoSolution(message) => write!(f, "No solution: {message}"),
+            SolveError::Unsupported(message) => write!(f, "Unsupported input: {message}"),
+            SolveError::Cancelled => write!(f, "Cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
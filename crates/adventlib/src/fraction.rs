@@ -1,72 +1,476 @@
-use super::util::gcd;
+use std::{
+    cmp::Ordering,
+    fmt::{self, Debug, Display},
+    hash::Hash,
+    iter::{Product, Sum},
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+    str::FromStr,
+};
 
-// Represents a fraction with 0=numerator 1=denominator
-#[derive(Clone, Copy)]
-pub struct Fraction(i32, i32);
+use crate::error::SolveError;
 
-impl Fraction {
-    pub const fn signum(&self) -> i32 {
-        return self.0.signum() * self.1.signum();
+// The integers a fraction can be built from. Arithmetic is checked so that
+// fractions can report overflow rather than silently wrap.
+pub trait Integer: Clone + Eq + Ord + Hash + Debug + Display + From<i32> {
+    fn checked_add(&self, rhs: &Self) -> Option<Self>;
+    fn checked_sub(&self, rhs: &Self) -> Option<Self>;
+    fn checked_mul(&self, rhs: &Self) -> Option<Self>;
+    fn checked_div(&self, rhs: &Self) -> Option<Self>; // Rounds towards zero
+    fn checked_rem(&self, rhs: &Self) -> Option<Self>;
+    fn checked_neg(&self) -> Option<Self>;
+    fn to_f64(&self) -> f64;
+
+    fn zero() -> Self {
+        Self::from(0)
     }
 
-    pub const fn is_integer(&self) -> bool {
-        (self.0 % self.1) == 0
+    fn one() -> Self {
+        Self::from(1)
     }
 
-    pub const fn ceil(&self) -> i32 {
-        (self.0 / self.1) + (!self.is_integer() as i32)
+    fn is_zero(&self) -> bool {
+        *self == Self::zero()
     }
 
-    pub const fn floor(&self) -> i32 {
-        self.0 / self.1
+    fn is_negative(&self) -> bool {
+        *self < Self::zero()
     }
 
-    pub const fn reduce(&self) -> Self {
-        if self.0 == 0 || self.1 == 0 {
-            Fraction(0, 1)
+    // Never negative, and zero only if both are
+    fn gcd(&self, other: &Self) -> Option<Self> {
+        let (mut a, mut b) = (self.clone(), other.clone());
+        while !b.is_zero() {
+            let r = a.checked_rem(&b)?;
+            (a, b) = (b, r);
+        }
+        if a.is_negative() {
+            a.checked_neg()
         } else {
-            let x = gcd(self.0.abs(), self.1.abs()) * self.1.signum();
-            Fraction(self.0 / x, self.1 / x)
+            Some(a)
+        }
+    }
+}
+
+macro_rules! integer_impl {
+    ( $( $t:ty ),* ) => {
+        $(
+            impl Integer for $t {
+                fn checked_add(&self, rhs: &Self) -> Option<Self> {
+                    <$t>::checked_add(*self, *rhs)
+                }
+
+                fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+                    <$t>::checked_sub(*self, *rhs)
+                }
+
+                fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+                    <$t>::checked_mul(*self, *rhs)
+                }
+
+                fn checked_div(&self, rhs: &Self) -> Option<Self> {
+                    <$t>::checked_div(*self, *rhs)
+                }
+
+                fn checked_rem(&self, rhs: &Self) -> Option<Self> {
+                    <$t>::checked_rem(*self, *rhs)
+                }
+
+                fn checked_neg(&self) -> Option<Self> {
+                    <$t>::checked_neg(*self)
+                }
+
+                fn to_f64(&self) -> f64 {
+                    *self as f64
+                }
+            }
+        )*
+    };
+}
+
+integer_impl!(i32, i64, i128);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FractionError {
+    Overflow,
+    DivideByZero,
+}
+
+impl Display for FractionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FractionError::Overflow => write!(f, "Fraction overflow"),
+            FractionError::DivideByZero => write!(f, "Fraction divided by zero"),
         }
     }
 }
 
-impl PartialEq for Fraction {
-    fn eq(&self, other: &Self) -> bool {
-        self.0 * other.1 == other.0 * self.1
+impl std::error::Error for FractionError {}
+
+impl From<FractionError> for SolveError {
+    fn from(err: FractionError) -> Self {
+        SolveError::unsupported(err.to_string())
     }
 }
 
-impl Eq for Fraction {}
+// Represents a fraction with 0=numerator 1=denominator. Fractions are always
+// in lowest terms with a positive denominator, which keeps the numbers small
+// and lets equal values compare and hash equal field by field.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Fraction<T>(T, T);
+
+impl<T: Integer> Fraction<T> {
+    pub fn checked_new(numerator: T, denominator: T) -> Result<Self, FractionError> {
+        if denominator.is_zero() {
+            return Err(FractionError::DivideByZero);
+        }
+        let reduce = || {
+            let g = numerator.gcd(&denominator)?;
+            let (n, d) = (numerator.checked_div(&g)?, denominator.checked_div(&g)?);
+            match d.is_negative() {
+                true => Some(Fraction(n.checked_neg()?, d.checked_neg()?)),
+                false => Some(Fraction(n, d)),
+            }
+        };
+        reduce().ok_or(FractionError::Overflow)
+    }
+
+    pub fn numerator(&self) -> &T {
+        &self.0
+    }
+
+    pub fn denominator(&self) -> &T {
+        &self.1
+    }
+
+    pub fn zero() -> Self {
+        Fraction(T::zero(), T::one())
+    }
+
+    pub fn one() -> Self {
+        Fraction(T::one(), T::one())
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    pub fn signum(&self) -> i32 {
+        match (self.0.is_zero(), self.0.is_negative()) {
+            (true, _) => 0,
+            (false, true) => -1,
+            (false, false) => 1,
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.1 == T::one()
+    }
+
+    // Rounding is exact for every sign and never overflows, as a fraction which
+    // isn't an integer has a denominator of at least 2 and so its whole part
+    // is at most half the largest integer, leaving room to step past it.
+    pub fn floor(&self) -> T {
+        self.split().0
+    }
+
+    pub fn ceil(&self) -> T {
+        match self.split() {
+            (q, r) if r.is_zero() => q,
+            (q, _) => or_overflow(q.checked_add(&T::one())),
+        }
+    }
+
+    pub fn trunc(&self) -> T {
+        or_overflow(self.0.checked_div(&self.1))
+    }
+
+    // Nearest integer with halves rounded away from zero, like f64::round
+    pub fn round(&self) -> T {
+        let (q, r) = self.split();
+        let rest = or_overflow(self.1.checked_sub(&r));
+        match r.cmp(&rest) {
+            Ordering::Less => q,
+            Ordering::Equal if self.0.is_negative() => q,
+            _ => or_overflow(q.checked_add(&T::one())),
+        }
+    }
+
+    // Only divides each denominator by their gcd rather than multiplying them
+    pub fn checked_add(&self, rhs: &Self) -> Result<Self, FractionError> {
+        let sum = || {
+            let g = self.1.gcd(&rhs.1)?;
+            let (b, d) = (self.1.checked_div(&g)?, rhs.1.checked_div(&g)?);
+            let n = (self.0.checked_mul(&d)?).checked_add(&rhs.0.checked_mul(&b)?)?;
+            Some((n, b.checked_mul(&rhs.1)?))
+        };
+        let (n, d) = sum().ok_or(FractionError::Overflow)?;
+        Self::checked_new(n, d)
+    }
+
+    pub fn checked_sub(&self, rhs: &Self) -> Result<Self, FractionError> {
+        self.checked_add(&rhs.checked_neg()?)
+    }
+
+    // Cancels common factors across both fractions before multiplying
+    pub fn checked_mul(&self, rhs: &Self) -> Result<Self, FractionError> {
+        let product = || {
+            let (g1, g2) = (self.0.gcd(&rhs.1)?, rhs.0.gcd(&self.1)?);
+            let n = (self.0.checked_div(&g1)?).checked_mul(&rhs.0.checked_div(&g2)?)?;
+            let d = (self.1.checked_div(&g2)?).checked_mul(&rhs.1.checked_div(&g1)?)?;
+            Some((n, d))
+        };
+        let (n, d) = product().ok_or(FractionError::Overflow)?;
+        Self::checked_new(n, d)
+    }
+
+    pub fn checked_div(&self, rhs: &Self) -> Result<Self, FractionError> {
+        self.checked_mul(&rhs.checked_recip()?)
+    }
+
+    pub fn checked_recip(&self) -> Result<Self, FractionError> {
+        Self::checked_new(self.1.clone(), self.0.clone())
+    }
 
-impl PartialOrd for Fraction {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+    pub fn recip(&self) -> Self {
+        or_panic(self.checked_recip())
+    }
+
+    pub fn abs(&self) -> Self {
+        match self.0.is_negative() {
+            true => -self,
+            false => self.clone(),
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.0.to_f64() / self.1.to_f64()
+    }
+
+    pub fn checked_neg(&self) -> Result<Self, FractionError> {
+        match self.0.checked_neg() {
+            Some(n) => Ok(Fraction(n, self.1.clone())),
+            None => Err(FractionError::Overflow),
+        }
+    }
+
+    // Whole part rounded down and the remaining numerator over the same
+    // denominator, which is never negative
+    fn split(&self) -> (T, T) {
+        let q = self.trunc();
+        let r = or_overflow(self.0.checked_sub(&or_overflow(q.checked_mul(&self.1))));
+        match r.is_negative() {
+            true => (
+                or_overflow(q.checked_sub(&T::one())),
+                or_overflow(r.checked_add(&self.1)),
+            ),
+            false => (q, r),
+        }
+    }
+}
+
+fn or_overflow<T>(value: Option<T>) -> T {
+    value.expect("Fraction overflow")
+}
+
+// Operators panic instead of wrapping when a result doesn't fit, use the
+// checked methods to handle that as an error
+fn or_panic<T>(result: Result<Fraction<T>, FractionError>) -> Fraction<T> {
+    result.unwrap_or_else(|err| panic!("{}", err))
+}
+
+// Continued fractions and the Stern-Brocot tree. Convergents are always in
+// lowest terms, as consecutive ones differ by exactly 1/(k(n) k(n-1)).
+impl<T: Integer> Fraction<T> {
+    // Terms [a0; a1, a2, ...] from Euclid's algorithm, where every term after
+    // the first is positive and the last is above 1 unless it's the only one
+    pub fn continued_fraction(&self) -> Vec<T> {
+        let mut terms = vec![];
+        let (mut n, mut d) = (self.0.clone(), self.1.clone());
+        while !d.is_zero() {
+            let (q, r) = Fraction(n, d.clone()).split();
+            terms.push(q);
+            (n, d) = (d, r);
+        }
+        terms
+    }
+
+    // Fails for no terms, or terms which lead to a zero denominator
+    pub fn from_continued_fraction(terms: &[T]) -> Result<Self, FractionError> {
+        let (h, k) = (convergent_pairs(terms).last().cloned())
+            .unwrap_or(Some((T::one(), T::zero())))
+            .ok_or(FractionError::Overflow)?;
+        Self::checked_new(h, k)
+    }
+
+    // Successively closer approximations, ending with the fraction itself
+    pub fn convergents(&self) -> Vec<Self> {
+        let terms = self.continued_fraction();
+        (convergent_pairs(&terms).into_iter())
+            .map(|pair| {
+                let (h, k) = or_overflow(pair);
+                Fraction(h, k)
+            })
+            .collect()
+    }
+
+    // The closest fraction with a denominator of at most max_denominator,
+    // found by walking down the Stern-Brocot tree a whole term at a time.
+    // The nearest is either the last convergent that fits or the
+    // semiconvergent just before the next one, ties going to the convergent.
+    pub fn best_approximation(&self, max_denominator: &T) -> Self {
+        assert!(
+            *max_denominator >= T::one(),
+            "Approximations need a positive denominator"
+        );
+        if self.1 <= *max_denominator {
+            return self.clone();
+        }
+        let ((mut h0, mut k0), (mut h1, mut k1)) = ((T::zero(), T::one()), (T::one(), T::zero()));
+        for a in self.continued_fraction() {
+            let k2 = or_overflow(mul_add(&a, &k1, &k0));
+            if k2 > *max_denominator {
+                let m = or_overflow(
+                    max_denominator
+                        .checked_sub(&k0)
+                        .and_then(|r| r.checked_div(&k1)),
+                );
+                let semi = Fraction(
+                    or_overflow(mul_add(&m, &h1, &h0)),
+                    or_overflow(mul_add(&m, &k1, &k0)),
+                );
+                let convergent = Fraction(h1, k1);
+                return match (&semi - self).abs() < (&convergent - self).abs() {
+                    true => semi,
+                    false => convergent,
+                };
+            }
+            let h2 = or_overflow(mul_add(&a, &h1, &h0));
+            ((h0, k0), (h1, k1)) = ((h1, k1), (h2, k2));
+        }
+        unreachable!("The last convergent is the fraction itself")
+    }
+
+    // The fraction between two neighbours in the Stern-Brocot tree or a Farey
+    // sequence, (a + c) / (b + d), which lies between any two fractions
+    pub fn checked_mediant(&self, other: &Self) -> Result<Self, FractionError> {
+        let sum = || Some((self.0.checked_add(&other.0)?, self.1.checked_add(&other.1)?));
+        let (n, d) = sum().ok_or(FractionError::Overflow)?;
+        Self::checked_new(n, d)
+    }
+
+    pub fn mediant(&self, other: &Self) -> Self {
+        or_panic(self.checked_mediant(other))
+    }
+
+    // Every fraction from 0 to 1 with a denominator of at most order, in
+    // increasing order
+    pub fn farey(order: T) -> Farey<T> {
+        let first = match order >= T::one() {
+            true => Some((Self::zero(), Fraction(T::one(), order.clone()))),
+            false => None,
+        };
+        Farey {
+            order: order,
+            next: first,
+        }
+    }
+}
+
+// Numerators and denominators of the convergents of the given terms from
+// h(n) = a(n) h(n-1) + h(n-2) and likewise for k, None once they overflow
+fn convergent_pairs<T: Integer>(terms: &[T]) -> Vec<Option<(T, T)>> {
+    let mut pairs = vec![];
+    let ((mut h0, mut k0), (mut h1, mut k1)) = ((T::zero(), T::one()), (T::one(), T::zero()));
+    for a in terms {
+        let next = mul_add(a, &h1, &h0).zip(mul_add(a, &k1, &k0));
+        pairs.push(next.clone());
+        let Some((h2, k2)) = next else { break };
+        ((h0, k0), (h1, k1)) = ((h1, k1), (h2, k2));
+    }
+    pairs
+}
+
+fn mul_add<T: Integer>(a: &T, b: &T, c: &T) -> Option<T> {
+    a.checked_mul(b)?.checked_add(c)
+}
+
+// Iterates a Farey sequence from each pair of neighbours, as the next
+// denominator is the largest that fits of the form k d - b
+pub struct Farey<T> {
+    order: T,
+    next: Option<(Fraction<T>, Fraction<T>)>,
+}
+
+impl<T: Integer> Iterator for Farey<T> {
+    type Item = Fraction<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (current, following) = self.next.take()?;
+        if current != Fraction::one() {
+            let (Fraction(a, b), Fraction(c, d)) = (&current, &following);
+            let step = || {
+                let k = (self.order.checked_add(b)?).checked_div(d)?;
+                let n = k.checked_mul(c)?.checked_sub(a)?;
+                Some(Fraction(n, k.checked_mul(d)?.checked_sub(b)?))
+            };
+            self.next = Some((following.clone(), or_overflow(step())));
+        }
+        Some(current)
+    }
+}
+
+impl<T: Integer> PartialOrd for Fraction<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
-impl Ord for Fraction {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        let sign = (self - other).signum();
-        if sign > 0 {
-            std::cmp::Ordering::Greater
-        } else if sign < 0 {
-            std::cmp::Ordering::Less
-        } else {
-            std::cmp::Ordering::Equal
+// Compares whole parts and then the reciprocals of what remains, as with
+// continued fractions, so nothing is multiplied and nothing can overflow
+impl<T: Integer> Ord for Fraction<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (mut a, mut b) = (self.clone(), other.clone());
+        let mut flipped = false;
+        loop {
+            let ((qa, ra), (qb, rb)) = (a.split(), b.split());
+            let ordering = match (qa.cmp(&qb), ra.is_zero(), rb.is_zero()) {
+                (Ordering::Equal, true, true) => Ordering::Equal,
+                (Ordering::Equal, true, false) => Ordering::Less,
+                (Ordering::Equal, false, true) => Ordering::Greater,
+                (Ordering::Equal, false, false) => {
+                    (a, b) = (Fraction(a.1, ra), Fraction(b.1, rb));
+                    flipped = !flipped;
+                    continue;
+                }
+                (ordering, _, _) => ordering,
+            };
+            return if flipped {
+                ordering.reverse()
+            } else {
+                ordering
+            };
         }
     }
 }
 
-impl From<i32> for Fraction {
-    fn from(value: i32) -> Self {
-        Fraction(value, 1)
+impl<T: Integer> From<T> for Fraction<T> {
+    fn from(value: T) -> Self {
+        Fraction(value, T::one())
     }
 }
 
-impl std::fmt::Debug for Fraction {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.1 == 1 {
+// Fails rather than divide by zero or overflow while reducing
+impl<T: Integer> TryFrom<(T, T)> for Fraction<T> {
+    type Error = FractionError;
+
+    fn try_from((numerator, denominator): (T, T)) -> Result<Self, Self::Error> {
+        Self::checked_new(numerator, denominator)
+    }
+}
+
+impl<T: Integer> Display for Fraction<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_integer() {
             write![f, "{}", self.0]
         } else {
             write![f, "{}/{}", self.0, self.1]
@@ -74,78 +478,539 @@ impl std::fmt::Debug for Fraction {
     }
 }
 
-macro_rules! fraction_i32_ops {
+impl<T: Integer> Debug for Fraction<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFractionError(String);
+
+impl Display for ParseFractionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid fraction: [{}]", self.0)
+    }
+}
+
+impl std::error::Error for ParseFractionError {}
+
+// Reads integers such as "-2" and fractions such as "3/4" or "6/-8", which are
+// reduced like any other fraction
+impl<T: Integer + FromStr> FromStr for Fraction<T> {
+    type Err = ParseFractionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseFractionError(s.to_string());
+        let (numerator, denominator) = s.split_once('/').unwrap_or((s, "1"));
+        let numerator = numerator.trim().parse().map_err(|_| invalid())?;
+        let denominator = denominator.trim().parse().map_err(|_| invalid())?;
+        Self::checked_new(numerator, denominator).map_err(|_| invalid())
+    }
+}
+
+impl<T: Integer> PartialEq<T> for Fraction<T> {
+    fn eq(&self, rhs: &T) -> bool {
+        self.is_integer() && self.0 == *rhs
+    }
+}
+
+impl<T: Integer> Neg for Fraction<T> {
+    type Output = Fraction<T>;
+
+    fn neg(self) -> Self::Output {
+        or_panic(self.checked_neg())
+    }
+}
+
+impl<T: Integer> Neg for &Fraction<T> {
+    type Output = Fraction<T>;
+
+    fn neg(self) -> Self::Output {
+        or_panic(self.checked_neg())
+    }
+}
+
+macro_rules! fraction_integer_ops {
     ($lhs:ty) => {
-        impl std::ops::Mul<i32> for $lhs {
-            type Output = Fraction;
+        impl<T: Integer> Add<T> for $lhs {
+            type Output = Fraction<T>;
 
-            fn mul(self, rhs: i32) -> Self::Output {
-                Fraction(self.0 * rhs, self.1)
+            fn add(self, rhs: T) -> Self::Output {
+                or_panic(self.checked_add(&Fraction::from(rhs)))
             }
         }
 
-        impl std::ops::Div<i32> for $lhs {
-            type Output = Fraction;
+        impl<T: Integer> Sub<T> for $lhs {
+            type Output = Fraction<T>;
 
-            fn div(self, rhs: i32) -> Self::Output {
-                Fraction(self.0, self.1 * rhs)
+            fn sub(self, rhs: T) -> Self::Output {
+                or_panic(self.checked_sub(&Fraction::from(rhs)))
             }
         }
-    };
-}
 
-impl std::cmp::PartialEq<i32> for Fraction {
-    fn eq(&self, rhs: &i32) -> bool {
-        self.0 == self.1 * rhs
-    }
+        impl<T: Integer> Mul<T> for $lhs {
+            type Output = Fraction<T>;
+
+            fn mul(self, rhs: T) -> Self::Output {
+                or_panic(self.checked_mul(&Fraction::from(rhs)))
+            }
+        }
+
+        impl<T: Integer> Div<T> for $lhs {
+            type Output = Fraction<T>;
+
+            fn div(self, rhs: T) -> Self::Output {
+                or_panic(self.checked_div(&Fraction::from(rhs)))
+            }
+        }
+    };
 }
 
-fraction_i32_ops!(Fraction);
-fraction_i32_ops!(&Fraction);
+fraction_integer_ops!(Fraction<T>);
+fraction_integer_ops!(&Fraction<T>);
 
 macro_rules! fraction_fraction_ops {
     ($lhs:ty, $rhs:ty) => {
-        impl std::ops::Mul<$rhs> for $lhs {
-            type Output = Fraction;
+        impl<T: Integer> Mul<$rhs> for $lhs {
+            type Output = Fraction<T>;
 
             fn mul(self, rhs: $rhs) -> Self::Output {
-                Fraction(self.0 * rhs.0, self.1 * rhs.1)
+                or_panic(self.checked_mul(&rhs))
             }
         }
 
-        impl std::ops::Div<$rhs> for $lhs {
-            type Output = Fraction;
+        impl<T: Integer> Div<$rhs> for $lhs {
+            type Output = Fraction<T>;
 
             fn div(self, rhs: $rhs) -> Self::Output {
-                Fraction(self.0 * rhs.1, self.1 * rhs.0)
+                or_panic(self.checked_div(&rhs))
             }
         }
 
-        impl std::ops::Add<$rhs> for $lhs {
-            type Output = Fraction;
+        impl<T: Integer> Add<$rhs> for $lhs {
+            type Output = Fraction<T>;
 
             fn add(self, rhs: $rhs) -> Self::Output {
-                Fraction(self.0 * rhs.1 + rhs.0 * self.1, self.1 * rhs.1)
+                or_panic(self.checked_add(&rhs))
             }
         }
 
-        impl std::ops::Sub<$rhs> for $lhs {
-            type Output = Fraction;
+        impl<T: Integer> Sub<$rhs> for $lhs {
+            type Output = Fraction<T>;
 
             fn sub(self, rhs: $rhs) -> Self::Output {
-                Fraction(self.0 * rhs.1 - rhs.0 * self.1, self.1 * rhs.1)
+                or_panic(self.checked_sub(&rhs))
             }
         }
     };
 }
 
-impl std::iter::Sum for Fraction {
+fraction_fraction_ops!(Fraction<T>, Fraction<T>);
+fraction_fraction_ops!(&Fraction<T>, Fraction<T>);
+fraction_fraction_ops!(Fraction<T>, &Fraction<T>);
+fraction_fraction_ops!(&Fraction<T>, &Fraction<T>);
+
+macro_rules! fraction_assign_ops {
+    ($rhs:ty) => {
+        impl<T: Integer> AddAssign<$rhs> for Fraction<T> {
+            fn add_assign(&mut self, rhs: $rhs) {
+                *self = or_panic(self.checked_add(&rhs));
+            }
+        }
+
+        impl<T: Integer> SubAssign<$rhs> for Fraction<T> {
+            fn sub_assign(&mut self, rhs: $rhs) {
+                *self = or_panic(self.checked_sub(&rhs));
+            }
+        }
+
+        impl<T: Integer> MulAssign<$rhs> for Fraction<T> {
+            fn mul_assign(&mut self, rhs: $rhs) {
+                *self = or_panic(self.checked_mul(&rhs));
+            }
+        }
+
+        impl<T: Integer> DivAssign<$rhs> for Fraction<T> {
+            fn div_assign(&mut self, rhs: $rhs) {
+                *self = or_panic(self.checked_div(&rhs));
+            }
+        }
+    };
+}
+
+fraction_assign_ops!(Fraction<T>);
+fraction_assign_ops!(&Fraction<T>);
+
+impl<T: Integer> Sum for Fraction<T> {
     fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
-        iter.fold(Fraction::from(0), |a, b| a + b)
+        iter.fold(Fraction::zero(), |a, b| a + b)
+    }
+}
+
+impl<'a, T: Integer> Sum<&'a Fraction<T>> for Fraction<T> {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.fold(Fraction::zero(), |a, b| a + b)
+    }
+}
+
+impl<T: Integer> Product for Fraction<T> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Fraction::one(), |a, b| a * b)
+    }
+}
+
+impl<'a, T: Integer> Product<&'a Fraction<T>> for Fraction<T> {
+    fn product<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.fold(Fraction::one(), |a, b| a * b)
     }
 }
 
-fraction_fraction_ops!(Fraction, Fraction);
-fraction_fraction_ops!(&Fraction, Fraction);
-fraction_fraction_ops!(Fraction, &Fraction);
-fraction_fraction_ops!(&Fraction, &Fraction);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frac(n: i64, d: i64) -> Fraction<i64> {
+        Fraction::checked_new(n, d).unwrap()
+    }
+
+    // Every sign of numerator and denominator, for whole numbers, halves and
+    // values either side of a half
+    fn cases() -> Vec<(i64, i64)> {
+        let magnitudes = [
+            (0, 1),
+            (0, 3),
+            (4, 1),
+            (6, 3),
+            (1, 2),
+            (5, 2),
+            (1, 3),
+            (2, 3),
+            (7, 4),
+            (9, 4),
+        ];
+        (magnitudes.iter())
+            .flat_map(|&(n, d)| [(n, d), (-n, d), (n, -d), (-n, -d)])
+            .collect()
+    }
+
+    #[test]
+    fn rounding_matches_f64_for_every_sign() {
+        for (n, d) in cases() {
+            let (f, x) = (frac(n, d), n as f64 / d as f64);
+            assert_eq!(f.floor(), x.floor() as i64, "floor({n}/{d})");
+            assert_eq!(f.ceil(), x.ceil() as i64, "ceil({n}/{d})");
+            assert_eq!(f.trunc(), x.trunc() as i64, "trunc({n}/{d})");
+            assert_eq!(f.round(), x.round() as i64, "round({n}/{d})");
+        }
+    }
+
+    #[test]
+    fn rounding_negative_halves() {
+        assert_eq!(frac(-1, 2).floor(), -1);
+        assert_eq!(frac(-1, 2).ceil(), 0);
+        assert_eq!(frac(-1, 2).trunc(), 0);
+        assert_eq!(frac(-1, 2).round(), -1);
+        assert_eq!(frac(1, -2).round(), -1);
+        assert_eq!(frac(-3, -2).round(), 2);
+    }
+
+    #[test]
+    fn rounding_at_the_limits() {
+        let min = Fraction::from(i32::MIN);
+        assert_eq!(
+            (min.floor(), min.ceil(), min.round()),
+            (i32::MIN, i32::MIN, i32::MIN)
+        );
+        let max = Fraction::from(i32::MAX);
+        assert_eq!(
+            (max.floor(), max.ceil(), max.round()),
+            (i32::MAX, i32::MAX, i32::MAX)
+        );
+        // The largest fraction which isn't an integer still rounds up safely
+        let half_max = Fraction::checked_new(i32::MAX, 2).unwrap();
+        assert_eq!(
+            (half_max.floor(), half_max.ceil(), half_max.round()),
+            (i32::MAX / 2, i32::MAX / 2 + 1, i32::MAX / 2 + 1)
+        );
+        assert_eq!(
+            Fraction::checked_new(i32::MIN, 3).unwrap().floor(),
+            (i32::MIN as f64 / 3.0).floor() as i32
+        );
+    }
+
+    #[test]
+    fn signs_are_canonical() {
+        for (n, d) in cases() {
+            let f = frac(n, d);
+            assert!(!f.denominator().is_negative(), "{n}/{d}");
+            assert_eq!(f.signum(), (n * d).signum() as i32, "{n}/{d}");
+            assert_eq!(f, frac(-n, -d));
+            assert_eq!(f.to_string().parse::<Fraction<i64>>(), Ok(f));
+        }
+        assert_eq!(frac(0, -5).to_string(), "0");
+        assert_eq!(frac(3, -6).to_string(), "-1/2");
+    }
+
+    #[test]
+    fn ordering_matches_f64_for_every_sign() {
+        for (a, b) in cases() {
+            for (c, d) in cases() {
+                let expected = (a as f64 / b as f64).partial_cmp(&(c as f64 / d as f64));
+                assert_eq!(
+                    frac(a, b).partial_cmp(&frac(c, d)),
+                    expected,
+                    "{a}/{b} vs {c}/{d}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn arithmetic_matches_f64_for_every_sign() {
+        for (a, b) in cases() {
+            for (c, d) in cases() {
+                let (x, y) = (frac(a, b), frac(c, d));
+                let (fx, fy) = (x.to_f64(), y.to_f64());
+                assert!(((x + y).to_f64() - (fx + fy)).abs() < 1e-9, "{x} + {y}");
+                assert!(((x - y).to_f64() - (fx - fy)).abs() < 1e-9, "{x} - {y}");
+                assert!(((x * y).to_f64() - (fx * fy)).abs() < 1e-9, "{x} * {y}");
+                if !y.is_zero() {
+                    assert!(((x / y).to_f64() - (fx / fy)).abs() < 1e-9, "{x} / {y}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn zero_denominators_are_errors() {
+        assert_eq!(
+            Fraction::checked_new(1, 0),
+            Err(FractionError::DivideByZero)
+        );
+        assert_eq!(
+            Fraction::checked_new(0, 0),
+            Err(FractionError::DivideByZero)
+        );
+        assert_eq!(
+            Fraction::try_from((-3, 0)),
+            Err(FractionError::DivideByZero)
+        );
+        assert_eq!(
+            frac(1, 2).checked_div(&Fraction::zero()),
+            Err(FractionError::DivideByZero)
+        );
+        assert_eq!(
+            Fraction::<i64>::zero().checked_recip(),
+            Err(FractionError::DivideByZero)
+        );
+        assert!("1/0".parse::<Fraction<i64>>().is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "Fraction divided by zero")]
+    fn dividing_by_zero_panics() {
+        let _ = frac(1, 2) / Fraction::zero();
+    }
+
+    #[test]
+    fn overflow_is_an_error() {
+        let max = Fraction::from(i32::MAX);
+        assert_eq!(
+            max.checked_add(&Fraction::one()),
+            Err(FractionError::Overflow)
+        );
+        assert_eq!(
+            max.checked_mul(&Fraction::from(2)),
+            Err(FractionError::Overflow)
+        );
+        assert_eq!(
+            Fraction::from(i32::MIN).checked_neg(),
+            Err(FractionError::Overflow)
+        );
+        // Common factors are cancelled before multiplying
+        let big = Fraction::checked_new(i32::MAX, 2).unwrap();
+        assert_eq!(
+            big.checked_mul(&Fraction::checked_new(2, i32::MAX).unwrap()),
+            Ok(Fraction::one())
+        );
+    }
+
+    #[test]
+    fn continued_fractions_round_trip() {
+        assert_eq!(frac(415, 93).continued_fraction(), vec![4, 2, 6, 7]);
+        assert_eq!(frac(-415, 93).continued_fraction(), vec![-5, 1, 1, 6, 7]);
+        assert_eq!(frac(-3, 1).continued_fraction(), vec![-3]);
+        assert_eq!(frac(0, 1).continued_fraction(), vec![0]);
+        for (n, d) in cases() {
+            let terms = frac(n, d).continued_fraction();
+            assert!(terms.iter().skip(1).all(|&a| a > 0), "{n}/{d}");
+            assert_eq!(Fraction::from_continued_fraction(&terms), Ok(frac(n, d)));
+        }
+        // Non-canonical terms still give the same value
+        assert_eq!(
+            Fraction::from_continued_fraction(&[4, 2, 6, 6, 1]),
+            Ok(frac(415, 93))
+        );
+    }
+
+    #[test]
+    fn continued_fractions_which_are_not_fractions() {
+        let empty: &[i64] = &[];
+        assert_eq!(
+            Fraction::from_continued_fraction(empty),
+            Err(FractionError::DivideByZero)
+        );
+        assert_eq!(
+            Fraction::from_continued_fraction(&[1_i64, 0]),
+            Err(FractionError::DivideByZero)
+        );
+        assert_eq!(
+            Fraction::from_continued_fraction(&[i32::MAX, 1, i32::MAX]),
+            Err(FractionError::Overflow)
+        );
+    }
+
+    #[test]
+    fn convergents_approach_the_fraction() {
+        let convergents = frac(415, 93).convergents();
+        assert_eq!(
+            convergents,
+            vec![frac(4, 1), frac(9, 2), frac(58, 13), frac(415, 93)]
+        );
+        assert_eq!(frac(-7, 2).convergents(), vec![frac(-4, 1), frac(-7, 2)]);
+    }
+
+    #[test]
+    fn best_approximations_of_pi() {
+        let pi = frac(3_141_592_653_589_793, 1_000_000_000_000_000);
+        assert_eq!(pi.best_approximation(&1), frac(3, 1));
+        assert_eq!(pi.best_approximation(&7), frac(22, 7));
+        assert_eq!(pi.best_approximation(&100), frac(311, 99));
+        assert_eq!(pi.best_approximation(&1000), frac(355, 113));
+        assert_eq!((-pi).best_approximation(&7), frac(-22, 7));
+        assert_eq!(frac(1, 3).best_approximation(&1000), frac(1, 3));
+    }
+
+    #[test]
+    fn best_approximations_match_a_search() {
+        for (n, d) in cases().into_iter().chain([(355, 113), (-17, 12), (99, 70)]) {
+            let x = frac(n, d);
+            for max in 1..8 {
+                let best = x.best_approximation(&max);
+                let closest = (1..=max)
+                    .flat_map(|q| [(x * q).floor(), (x * q).ceil()].map(|p| frac(p, q)))
+                    .map(|f| (f - x).abs())
+                    .min()
+                    .unwrap();
+                assert!(*best.denominator() <= max, "{x} to {max}");
+                assert_eq!((best - x).abs(), closest, "{x} to {max}");
+            }
+        }
+    }
+
+    #[test]
+    fn farey_sequences() {
+        let f5: Vec<_> = Fraction::farey(5_i64).map(|f| f.to_string()).collect();
+        let expected = "0 1/5 1/4 1/3 2/5 1/2 3/5 2/3 3/4 4/5 1";
+        assert_eq!(f5.join(" "), expected);
+        assert_eq!(
+            Fraction::farey(1_i64).collect::<Vec<_>>(),
+            vec![frac(0, 1), frac(1, 1)]
+        );
+        assert_eq!(Fraction::farey(0_i64).count(), 0);
+        // Neighbours are mediants of each other's neighbours, and the length is
+        // 1 + the sum of Euler's totient
+        let f8: Vec<_> = Fraction::farey(8_i64).collect();
+        assert_eq!(f8.len(), 1 + [1, 1, 2, 2, 4, 2, 6, 4].iter().sum::<usize>());
+        for w in f8.windows(3) {
+            assert_eq!(w[0].mediant(&w[2]), w[1]);
+        }
+    }
+
+    fn hash_of<H: Hash>(value: &H) -> u64 {
+        use std::hash::{BuildHasher, BuildHasherDefault, DefaultHasher};
+        BuildHasherDefault::<DefaultHasher>::default().hash_one(value)
+    }
+
+    #[test]
+    fn equal_fractions_hash_equal() {
+        assert_eq!(frac(2, 4), frac(-1, -2));
+        assert_eq!(hash_of(&frac(2, 4)), hash_of(&frac(-1, -2)));
+        let mut counts = std::collections::HashMap::new();
+        for (n, d) in [(2, 4), (-1, -2), (3, 6), (-1, 2), (1, -2), (0, 5), (0, -1)] {
+            *counts.entry(frac(n, d)).or_insert(0) += 1;
+        }
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts[&frac(1, 2)], 3);
+        assert_eq!(counts[&frac(-1, 2)], 2);
+        assert_eq!(counts[&Fraction::zero()], 2);
+    }
+
+    #[test]
+    fn negation() {
+        assert_eq!(-frac(1, 2), frac(-1, 2));
+        assert_eq!(-&frac(-3, 4), frac(3, 4));
+        assert_eq!(-Fraction::<i64>::zero(), Fraction::zero());
+        assert_eq!(
+            Fraction::from(i32::MIN).checked_neg(),
+            Err(FractionError::Overflow)
+        );
+    }
+
+    #[test]
+    fn parsing_and_display() {
+        let parse = |s: &str| s.parse::<Fraction<i64>>();
+        assert_eq!(parse("3/4"), Ok(frac(3, 4)));
+        assert_eq!(parse(" -2 "), Ok(frac(-2, 1)));
+        assert_eq!(parse("6/-8"), Ok(frac(-3, 4)));
+        assert_eq!(parse("-6 / -8"), Ok(frac(3, 4)));
+        for bad in ["", "/", "1/", "/2", "1/2/3", "a/b", "1.5", "1/0"] {
+            assert_eq!(parse(bad), Err(ParseFractionError(bad.to_string())));
+        }
+        assert_eq!(frac(-6, 8).to_string(), "-3/4");
+        assert_eq!(frac(8, 4).to_string(), "2");
+        assert_eq!(format!("{:?}", frac(1, -3)), "-1/3");
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(Fraction::try_from((6_i64, -4)), Ok(frac(-3, 2)));
+        assert_eq!(Fraction::from(7_i64), frac(7, 1));
+        assert_eq!(frac(-3, 4).to_f64(), -0.75);
+        assert_eq!(frac(1, 3).to_f64(), 1.0 / 3.0);
+        assert_eq!(frac(-3, 4).abs(), frac(3, 4));
+        assert_eq!(frac(3, 4).abs(), frac(3, 4));
+        assert_eq!(frac(-3, 4).recip(), frac(-4, 3));
+        assert_eq!(frac(5, 1).recip(), frac(1, 5));
+        assert_eq!(frac(6, 3), 2);
+        assert_ne!(frac(5, 2), 2);
+    }
+
+    #[test]
+    fn assignment_operators() {
+        let mut x = frac(1, 2);
+        x += frac(1, 3);
+        assert_eq!(x, frac(5, 6));
+        x -= &frac(1, 6);
+        assert_eq!(x, frac(2, 3));
+        x *= frac(-3, 4);
+        assert_eq!(x, frac(-1, 2));
+        x /= &frac(-1, 4);
+        assert_eq!(x, frac(2, 1));
+        assert_eq!(x + 1 - 2, frac(1, 1));
+        assert_eq!(&x * 3 / 4, frac(3, 2));
+    }
+
+    #[test]
+    fn sums_and_products() {
+        let values = [frac(1, 2), frac(-1, 3), frac(3, 4)];
+        assert_eq!(values.iter().sum::<Fraction<i64>>(), frac(11, 12));
+        assert_eq!(values.into_iter().sum::<Fraction<i64>>(), frac(11, 12));
+        assert_eq!(values.iter().product::<Fraction<i64>>(), frac(-1, 8));
+        assert_eq!(values.into_iter().product::<Fraction<i64>>(), frac(-1, 8));
+        let none: [Fraction<i64>; 0] = [];
+        assert_eq!(none.iter().sum::<Fraction<i64>>(), Fraction::zero());
+        assert_eq!(none.iter().product::<Fraction<i64>>(), Fraction::one());
+    }
+}
@@ -1,164 +1,203 @@
 use std::{collections::HashMap, fmt::Debug};
 
-use crate::fraction::Fraction;
+use crate::{
+    error::SolveError,
+    fraction::{Fraction, FractionError, Integer},
+    log,
+    log::Level,
+};
 
 // Represents a linear equation of the form a_1 * x_1 + a_2 * x_2 + .. a_n * x_n = b
-pub struct LinearEquation {
-    pub a: Vec<Fraction>,
-    pub b: Fraction,
+pub struct LinearEquation<T> {
+    pub a: Vec<Fraction<T>>,
+    pub b: Fraction<T>,
 }
 
-impl Debug for LinearEquation {
+impl<T: Integer> Debug for LinearEquation<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write![f, "{:?} * X = {:?}", self.a, self.b]
     }
 }
 
-impl std::ops::Add<&LinearEquation> for &LinearEquation {
-    type Output = LinearEquation;
-
-    fn add(self, rhs: &LinearEquation) -> Self::Output {
-        LinearEquation {
+impl<T: Integer> LinearEquation<T> {
+    // Row operations used by elimination, which fail rather than overflow
+    fn checked_add(&self, rhs: &LinearEquation<T>) -> Result<LinearEquation<T>, FractionError> {
+        Ok(LinearEquation {
             a: (self.a.iter())
                 .zip(rhs.a.iter())
-                .map(|(a, b)| (a + b).reduce())
-                .collect(),
-            b: (self.b + rhs.b).reduce(),
-        }
+                .map(|(a, b)| a.checked_add(b))
+                .collect::<Result<_, _>>()?,
+            b: self.b.checked_add(&rhs.b)?,
+        })
     }
-}
-
-impl std::ops::Mul<Fraction> for &LinearEquation {
-    type Output = LinearEquation;
 
-    fn mul(self, rhs: Fraction) -> Self::Output {
-        LinearEquation {
-            a: self.a.iter().map(|c| (c * rhs).reduce()).collect(),
-            b: (self.b * rhs).reduce(),
-        }
+    fn checked_mul(&self, rhs: &Fraction<T>) -> Result<LinearEquation<T>, FractionError> {
+        Ok(LinearEquation {
+            a: (self.a.iter())
+                .map(|c| c.checked_mul(rhs))
+                .collect::<Result<_, _>>()?,
+            b: self.b.checked_mul(rhs)?,
+        })
     }
-}
 
-impl LinearEquation {
-    fn solve(&self, index: usize, values: &Vec<Fraction>) -> Fraction {
-        if self.a[index] == 0 {
-            return Fraction::from(0);
+    #[allow(clippy::ptr_arg)]
+    fn solve(&self, index: usize, values: &Vec<Fraction<T>>) -> Result<Fraction<T>, FractionError> {
+        if self.a[index].is_zero() {
+            return Ok(Fraction::zero());
         }
-        let others: Fraction = self
+        let others = self
             .get_vars_except(index)
-            .map(|i| &self.a[i] * values[i])
-            .sum::<Fraction>();
-        return ((self.b - others) / self.a[index]).reduce();
+            .try_fold(Fraction::zero(), |sum, i| {
+                sum.checked_add(&self.a[i].checked_mul(&values[i])?)
+            })?;
+        (self.b.checked_sub(&others)?).checked_div(&self.a[index])
     }
 
-    fn get_implied_bound(&self, index: usize, bounds: &Vec<Bound>) -> Bound {
-        if self.a[index] == 0 {
-            return Bound::DEFAULT;
+    #[allow(clippy::ptr_arg)]
+    fn get_implied_bound(
+        &self,
+        index: usize,
+        bounds: &Vec<Bound<T>>,
+    ) -> Result<Bound<T>, FractionError> {
+        if self.a[index].is_zero() {
+            return Ok(Bound::DEFAULT);
         }
         let other_bound = self
             .get_vars_except(index)
-            .map(|i| bounds[i] * self.a[i])
-            .fold(Bound::point(0.into()), |a, b| a + b);
-        (other_bound * (-1).into() + self.b) * (Fraction::from(1) / self.a[index])
+            .try_fold(Bound::point(Fraction::zero()), |sum, i| {
+                sum.checked_add(&bounds[i].checked_mul(&self.a[i])?)
+            })?;
+        (other_bound.checked_mul(&-Fraction::one())?)
+            .checked_add(&Bound::point(self.b.clone()))?
+            .checked_mul(&self.a[index].checked_recip()?)
     }
 
     fn get_vars(&self) -> impl Iterator<Item = usize> {
         (self.a.iter().enumerate())
-            .filter(|(_, a)| **a != 0)
+            .filter(|(_, a)| !a.is_zero())
             .map(|(i, _)| i)
     }
 
     fn get_vars_except(&self, pivot: usize) -> impl Iterator<Item = usize> {
         self.get_vars()
-            .filter(move |i| *i != pivot && self.a[*i] != 0)
+            .filter(move |i| *i != pivot && !self.a[*i].is_zero())
     }
 }
 
-pub struct LinearSystem {
-    rows: Vec<LinearEquation>,
+pub struct LinearSystem<T> {
+    rows: Vec<LinearEquation<T>>,
 }
 
-impl FromIterator<LinearEquation> for LinearSystem {
-    fn from_iter<T: IntoIterator<Item = LinearEquation>>(iter: T) -> Self {
+impl<T> FromIterator<LinearEquation<T>> for LinearSystem<T> {
+    fn from_iter<I: IntoIterator<Item = LinearEquation<T>>>(iter: I) -> Self {
         let rows = iter.into_iter().collect();
         LinearSystem { rows: rows }
     }
 }
 
-#[derive(Clone, Copy)]
-pub struct Bound(pub Option<Fraction>, pub Option<Fraction>);
+#[derive(Clone)]
+pub struct Bound<T>(pub Option<Fraction<T>>, pub Option<Fraction<T>>);
 
-impl Bound {
-    const DEFAULT: Bound = Bound(None, None);
+impl<T: Integer> Bound<T> {
+    const DEFAULT: Bound<T> = Bound(None, None);
 
-    pub const fn point(value: Fraction) -> Self {
-        Bound(Some(value), Some(value))
+    pub fn point(value: Fraction<T>) -> Self {
+        Bound(Some(value.clone()), Some(value))
     }
 
-    pub fn closed_low(low: Fraction) -> Self {
+    pub fn closed_low(low: Fraction<T>) -> Self {
         Bound(Some(low), None)
     }
 
-    pub fn integer_range(&self, max: i32) -> impl Iterator<Item = Fraction> {
-        let low = self.0.map(|f| f.ceil()).unwrap_or(-max);
-        let high = self.1.map(|f| f.floor()).unwrap_or(max);
-        (low..(high + 1)).map(|i| Fraction::from(i))
+    pub fn integer_range(&self, max: i32) -> impl Iterator<Item = Fraction<T>> + use<T> {
+        let low = self.0.as_ref().map_or(T::from(-max), |f| f.ceil());
+        let high = self.1.as_ref().map_or(T::from(max), |f| f.floor());
+        std::iter::successors(Some(low), |i| i.checked_add(&T::one()))
+            .take_while(move |i| *i <= high)
+            .map(Fraction::from)
     }
 
-    pub fn contains(&self, val: Fraction) -> bool {
-        self.0.map_or(true, |l| l <= val) && self.1.map_or(true, |u| u >= val)
+    // Interval arithmetic for bound inference, which fails rather than overflow
+    pub fn checked_add(&self, rhs: &Bound<T>) -> Result<Bound<T>, FractionError> {
+        let add = |a: &Option<Fraction<T>>, b: &Option<Fraction<T>>| match (a, b) {
+            (Some(a), Some(b)) => a.checked_add(b).map(Some),
+            _ => Ok(None),
+        };
+        Ok(Bound(add(&self.0, &rhs.0)?, add(&self.1, &rhs.1)?))
+    }
+
+    pub fn checked_mul(&self, rhs: &Fraction<T>) -> Result<Bound<T>, FractionError> {
+        let mul = |f: &Option<Fraction<T>>| f.as_ref().map(|f| f.checked_mul(rhs)).transpose();
+        match rhs.signum() {
+            0 => Ok(Bound::point(Fraction::zero())),
+            1 => Ok(Bound(mul(&self.0)?, mul(&self.1)?)),
+            _ => Ok(Bound(mul(&self.1)?, mul(&self.0)?)),
+        }
+    }
+
+    #[allow(clippy::unnecessary_map_or)]
+    pub fn contains(&self, val: &Fraction<T>) -> bool {
+        self.0.as_ref().map_or(true, |l| l <= val) && self.1.as_ref().map_or(true, |u| u >= val)
     }
 }
 
 macro_rules! bound_ops {
     ($bound:ty) => {
-        impl std::ops::Add<Fraction> for $bound {
-            type Output = Bound;
+        impl<T: Integer> std::ops::Add<&Fraction<T>> for $bound {
+            type Output = Bound<T>;
 
-            fn add(self, rhs: Fraction) -> Self::Output {
+            fn add(self, rhs: &Fraction<T>) -> Self::Output {
                 Bound(
-                    self.0.map(|l| (l + rhs).reduce()),
-                    self.1.map(|u| (u + rhs).reduce()),
+                    self.0.as_ref().map(|l| l + rhs),
+                    self.1.as_ref().map(|u| u + rhs),
                 )
             }
         }
 
-        impl std::ops::Add<Bound> for $bound {
-            type Output = Bound;
+        impl<T: Integer> std::ops::Add<&Bound<T>> for $bound {
+            type Output = Bound<T>;
 
-            fn add(self, rhs: Bound) -> Self::Output {
+            fn add(self, rhs: &Bound<T>) -> Self::Output {
                 Bound(
                     self.0
-                        .map(|l| rhs.0.map(|ll| (l + ll).reduce()))
+                        .as_ref()
+                        .map(|l| rhs.0.as_ref().map(|ll| l + ll))
                         .unwrap_or(None),
                     self.1
-                        .map(|u| rhs.1.map(|uu| (u + uu).reduce()))
+                        .as_ref()
+                        .map(|u| rhs.1.as_ref().map(|uu| u + uu))
                         .unwrap_or(None),
                 )
             }
         }
 
-        impl std::ops::Mul<Fraction> for $bound {
-            type Output = Bound;
-
-            fn mul(self, rhs: Fraction) -> Self::Output {
-                if rhs == 0 {
-                    Bound::point(0.into())
-                } else if rhs > Fraction::from(0) {
-                    Bound(self.0.map(|l| l * rhs), self.1.map(|u| (u * rhs).reduce()))
+        impl<T: Integer> std::ops::Mul<&Fraction<T>> for $bound {
+            type Output = Bound<T>;
+
+            fn mul(self, rhs: &Fraction<T>) -> Self::Output {
+                if rhs.is_zero() {
+                    Bound::point(Fraction::zero())
+                } else if rhs.signum() > 0 {
+                    Bound(
+                        self.0.as_ref().map(|l| l * rhs),
+                        self.1.as_ref().map(|u| u * rhs),
+                    )
                 } else {
-                    Bound(self.1.map(|u| u * rhs), self.0.map(|l| (l * rhs).reduce()))
+                    Bound(
+                        self.1.as_ref().map(|u| u * rhs),
+                        self.0.as_ref().map(|l| l * rhs),
+                    )
                 }
             }
         }
 
-        impl std::ops::BitAnd<Bound> for $bound {
-            type Output = Option<Bound>;
+        impl<T: Integer> std::ops::BitAnd<&Bound<T>> for $bound {
+            type Output = Option<Bound<T>>;
 
-            fn bitand(self, rhs: Bound) -> Self::Output {
-                let low = self.0.max(rhs.0);
-                let high = [self.1, rhs.1].iter().filter_map(|v| v.clone()).min();
-                match (low, high) {
+            fn bitand(self, rhs: &Bound<T>) -> Self::Output {
+                let low = self.0.clone().max(rhs.0.clone());
+                let high = [&self.1, &rhs.1].into_iter().flatten().min().cloned();
+                match (&low, &high) {
                     (Some(l), Some(h)) => {
                         if h < l {
                             None
@@ -171,119 +210,132 @@ macro_rules! bound_ops {
             }
         }
 
-        impl std::ops::BitOr<Bound> for $bound {
-            type Output = Bound;
+        impl<T: Integer> std::ops::BitOr<&Bound<T>> for $bound {
+            type Output = Bound<T>;
 
-            fn bitor(self, rhs: Bound) -> Self::Output {
-                let low = [self.0, rhs.0].iter().filter_map(|v| v.clone()).min();
-                Bound(low, self.1.max(rhs.1))
+            fn bitor(self, rhs: &Bound<T>) -> Self::Output {
+                let low = [&self.0, &rhs.0].into_iter().flatten().min().cloned();
+                Bound(low, self.1.clone().max(rhs.1.clone()))
             }
         }
     };
 }
 
-bound_ops!(Bound);
-bound_ops!(&Bound);
+bound_ops!(Bound<T>);
+bound_ops!(&Bound<T>);
 
-impl std::fmt::Debug for Bound {
+impl<T: Integer> std::fmt::Debug for Bound<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
             "{}..{}",
-            self.0.map_or(String::new(), |l| format!("{:?}", l)),
-            self.1.map_or(String::new(), |u| format!("{:?}", u)),
+            self.0
+                .as_ref()
+                .map_or(String::new(), |l| format!("{:?}", l)),
+            self.1
+                .as_ref()
+                .map_or(String::new(), |u| format!("{:?}", u)),
         )
     }
 }
 
-pub struct ReducedRowEcheleon {
-    system: LinearSystem,
+pub struct ReducedRowEcheleon<T> {
+    system: LinearSystem<T>,
     pivots: HashMap<usize, usize>, // Column/variable index to row index
-    bounds: Vec<Bound>,
+    bounds: Vec<Bound<T>>,
     free: Vec<usize>,
 }
 
-impl Into<ReducedRowEcheleon> for LinearSystem {
-    fn into(mut self) -> ReducedRowEcheleon {
-        let row_count = self.rows.len();
-        let var_count = self.rows[0].a.len();
-        if self.rows.iter().any(|r| r.a.len() != var_count) {
-            panic!("Mismatched coefficient vectors");
+// Elimination fails with an overflow error rather than wrap when the integers
+// behind the fractions are too small for the system
+impl<T: Integer> TryFrom<LinearSystem<T>> for ReducedRowEcheleon<T> {
+    type Error = SolveError;
+
+    #[allow(clippy::filter_next, clippy::needless_borrow)]
+    fn try_from(mut system: LinearSystem<T>) -> Result<Self, Self::Error> {
+        let row_count = system.rows.len();
+        let var_count = system.rows.first().map_or(0, |r| r.a.len());
+        if system.rows.iter().any(|r| r.a.len() != var_count) {
+            return Err(SolveError::unsupported("Mismatched coefficient vectors"));
         }
         let mut pivots: HashMap<usize, usize> = HashMap::new(); // Column index to pivot row index
         for j in 0..var_count {
             // Pick the first row which is nonzero for column j and is not already a pivot row
             if let Some(i) = (pivots.len()..row_count)
-                .filter(|ii| self.rows[*ii].a[j] != 0)
+                .filter(|ii| !system.rows[*ii].a[j].is_zero())
                 .next()
             {
                 // Ensure column j has a value of 1
-                self.rows[i] = &self.rows[i] * (Fraction::from(1) / self.rows[i].a[j]);
+                let scale = system.rows[i].a[j].checked_recip()?;
+                system.rows[i] = system.rows[i].checked_mul(&scale)?;
                 // Ensure column j is 0 for all other rows
                 for ii in 0..row_count {
                     if ii == i {
                         continue;
                     }
-                    self.rows[ii] = &self.rows[ii]
-                        + &(&self.rows[i] * (Fraction::from(-1) * self.rows[ii].a[j]));
+                    let scale = system.rows[ii].a[j].checked_neg()?;
+                    system.rows[ii] =
+                        system.rows[ii].checked_add(&system.rows[i].checked_mul(&scale)?)?;
                 }
                 // Swap rows to partition pivots and unused rows
-                self.rows.swap(i, pivots.len());
+                system.rows.swap(i, pivots.len());
                 pivots.insert(j, pivots.len());
             }
         }
-        self.rows.retain(|r| {
-            let zero = r.a.iter().all(|c| *c == 0);
-            if zero && r.b != 0 {
-                panic!()
-            }
-            !zero
-        });
-        ReducedRowEcheleon {
-            system: self,
+        if (system.rows.iter()).any(|r| !r.b.is_zero() && r.a.iter().all(|c| c.is_zero())) {
+            return Err(SolveError::no_solution("Inconsistent linear system"));
+        }
+        system.rows.retain(|r| r.a.iter().any(|c| !c.is_zero()));
+        Ok(ReducedRowEcheleon {
+            system: system,
             free: (0..var_count)
                 .filter(|i| !pivots.contains_key(&i))
                 .collect(),
             pivots: pivots,
             bounds: vec![Bound::DEFAULT; var_count],
-        }
+        })
     }
 }
 
-impl ReducedRowEcheleon {
+impl<T: Integer> ReducedRowEcheleon<T> {
     pub const fn get_var_count(&self) -> usize {
         self.bounds.len()
     }
 
-    pub fn restrict_bound(&mut self, var: usize, bound: Bound) {
-        if let Some(new_bound) = &self.bounds[var] & bound {
+    pub fn restrict_bound(&mut self, var: usize, bound: Bound<T>) -> Result<(), SolveError> {
+        if let Some(new_bound) = &self.bounds[var] & &bound {
             self.bounds[var] = new_bound;
+            Ok(())
         } else {
-            panic!("No solution for {var}");
+            Err(SolveError::no_solution(format!("No solution for x{var}")))
         }
     }
 
-    pub fn infer_bounds(&mut self) {
+    #[allow(clippy::needless_borrow)]
+    pub fn infer_bounds(&mut self) -> Result<(), SolveError> {
         let mut pivots: Vec<&usize> = self.pivots.keys().collect();
         pivots.sort_by_key(|p| self.system.rows[self.pivots[p]].get_vars().count());
         for pivot in pivots {
             let row = &self.system.rows[self.pivots[&pivot]];
             for var in row.get_vars() {
-                let new_bound = row.get_implied_bound(var, &self.bounds);
-                if let Some(new_bounds) = &self.bounds[var] & new_bound {
+                let new_bound = row.get_implied_bound(var, &self.bounds)?;
+                if let Some(new_bounds) = &self.bounds[var] & &new_bound {
                     self.bounds[var] = new_bounds
                 } else {
-                    panic!("infer_bounds: No solution for {var}");
+                    return Err(SolveError::no_solution(format!(
+                        "infer_bounds: No solution for x{var}"
+                    )));
                 }
             }
         }
+        Ok(())
     }
 
     fn accumulate_free_options<'a>(
         &'a self,
         vars: usize,
         free: &mut Vec<usize>,
-    ) -> Box<dyn Iterator<Item = Vec<Fraction>> + 'a> {
+    ) -> Box<dyn Iterator<Item = Vec<Fraction<T>>> + 'a> {
         if let Some(i) = free.pop() {
             let base = self.accumulate_free_options(vars, free);
             let bounds = &self.bounds[i];
@@ -295,32 +347,41 @@ impl ReducedRowEcheleon {
                 })
             }))
         } else {
-            Box::new(std::iter::once(vec![Fraction::from(0); vars]))
+            Box::new(std::iter::once(vec![Fraction::zero(); vars]))
         }
     }
 
-    pub fn get_solutions(&self) -> impl Iterator<Item = Vec<i32>> {
+    // Integer solutions within the bounds, or an error once solving for the
+    // pivots overflows
+    pub fn get_solutions(&self) -> impl Iterator<Item = Result<Vec<T>, SolveError>> {
         self.accumulate_free_options(self.get_var_count(), &mut self.free.clone())
             .filter_map(|mut r| {
-                self.pivots.iter().for_each(|(p, rr)| {
-                    r[*p] = self.system.rows[*rr].solve(*p, &r).into();
-                });
+                for (p, rr) in &self.pivots {
+                    match self.system.rows[*rr].solve(*p, &r) {
+                        Ok(value) => r[*p] = value,
+                        Err(err) => return Some(Err(err.into())),
+                    }
+                }
                 if (self.pivots.iter())
-                    .all(|(p, _)| self.bounds[*p].contains(r[*p]) && r[*p].is_integer())
+                    .all(|(p, _)| self.bounds[*p].contains(&r[*p]) && r[*p].is_integer())
                 {
-                    return Some(r.iter().map(|f| f.floor()).collect());
+                    return Some(Ok(r.iter().map(|f| f.floor()).collect()));
                 }
                 None
             })
     }
 
-    #[allow(dead_code)]
+    // Dumps the rows and the bounds of each variable at debug level
     pub fn print_info(&self) {
+        if !log::enabled(Level::Debug) {
+            return;
+        }
         for row in &self.system.rows {
-            println!("{:?}", row);
+            log!(Debug, "{:?}", row);
         }
         for (i, b) in self.bounds.iter().enumerate() {
-            println!(
+            log!(
+                Debug,
                 "x{i} {b:?} {}",
                 if self.pivots.contains_key(&i) {
                     ""
@@ -331,3 +392,84 @@ impl ReducedRowEcheleon {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frac(n: i64, d: i64) -> Fraction<i64> {
+        Fraction::checked_new(n, d).unwrap()
+    }
+
+    fn range(bound: Bound<i64>) -> Vec<i64> {
+        bound.integer_range(3).map(|f| f.floor()).collect()
+    }
+
+    #[test]
+    fn integer_ranges_of_every_sign() {
+        assert_eq!(
+            range(Bound(Some(frac(-5, 2)), Some(frac(-1, 2)))),
+            vec![-2, -1]
+        );
+        assert_eq!(range(Bound(Some(frac(-1, 2)), Some(frac(1, 2)))), vec![0]);
+        assert_eq!(range(Bound(Some(frac(1, 2)), Some(frac(5, 2)))), vec![1, 2]);
+        assert_eq!(
+            range(Bound(Some(frac(-2, 1)), Some(frac(2, 1)))),
+            vec![-2, -1, 0, 1, 2]
+        );
+        assert_eq!(
+            range(Bound(Some(frac(-1, 3)), Some(frac(-1, 4)))),
+            Vec::<i64>::new()
+        );
+        assert_eq!(range(Bound(None, Some(frac(-3, 2)))), vec![-3, -2]);
+        assert_eq!(range(Bound(Some(frac(3, 2)), None)), vec![2, 3]);
+    }
+
+    #[test]
+    fn negative_bounds_are_enumerated() {
+        // 2x0 - 2x1 = -3 with -5/2 <= x0 <= -1/2 only has fractional solutions
+        let system: LinearSystem<i64> = [LinearEquation {
+            a: vec![frac(2, 1), frac(-2, 1)],
+            b: frac(-3, 1),
+        }]
+        .into_iter()
+        .collect();
+        let mut rre = ReducedRowEcheleon::try_from(system).unwrap();
+        rre.restrict_bound(0, Bound(Some(frac(-5, 2)), Some(frac(-1, 2))))
+            .unwrap();
+        assert_eq!(rre.get_solutions().count(), 0);
+    }
+
+    // x0 + 2 x1 = 2^31 - 1, which fits an i32 but twice the bound of x1 doesn't
+    fn overflowing_system() -> ReducedRowEcheleon<i32> {
+        let system: LinearSystem<i32> = [LinearEquation {
+            a: vec![Fraction::from(1), Fraction::from(2)],
+            b: Fraction::from(i32::MAX),
+        }]
+        .into_iter()
+        .collect();
+        ReducedRowEcheleon::try_from(system).unwrap()
+    }
+
+    #[test]
+    fn inferring_bounds_reports_overflow() {
+        let mut rre = overflowing_system();
+        rre.restrict_bound(0, Bound::closed_low(Fraction::zero()))
+            .unwrap();
+        rre.restrict_bound(
+            1,
+            Bound(Some(Fraction::zero()), Some(Fraction::from(i32::MAX))),
+        )
+        .unwrap();
+        assert_eq!(rre.infer_bounds(), Err(FractionError::Overflow.into()));
+    }
+
+    #[test]
+    fn solving_reports_overflow() {
+        let mut rre = overflowing_system();
+        rre.restrict_bound(1, Bound::point(Fraction::from(1 << 30)))
+            .unwrap();
+        let solutions: Vec<_> = rre.get_solutions().collect();
+        assert_eq!(solutions, vec![Err(FractionError::Overflow.into())]);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::vec::Vec3;
+use crate::{error::SolveError, util::parse, vec::Vec3};
 
 #[derive(PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
 pub struct ClosedInterval(pub i64, pub i64);
@@ -20,15 +20,11 @@ impl ClosedInterval {
         return (self.1 - self.0 + 1) as usize;
     }
 
-    pub fn parse(s: &str) -> Option<Self> {
-        if let Some((low, high)) = s.split_once('-') {
-            match (low.parse::<i64>(), high.parse::<i64>()) {
-                (Ok(start), Ok(end)) => return Some(ClosedInterval(start, end)),
-                _ => {}
-            }
-        }
-        println!("Bad interval string: '{}'", s);
-        return None;
+    // Reads "low-high", e.g. "3-5"
+    pub fn parse(s: &str) -> Result<Self, SolveError> {
+        let (low, high) =
+            (s.split_once('-')).ok_or_else(|| SolveError::parse(s, "Expected 'low-high'"))?;
+        Ok(ClosedInterval(parse(low)?, parse(high)?))
     }
 }
 
@@ -41,6 +37,7 @@ impl std::ops::Add<i64> for ClosedInterval {
 }
 
 impl std::fmt::Display for ClosedInterval {
+    #[allow(clippy::to_string_in_format_args)]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         return write!(f, "{}-{}", self.0.to_string(), self.1.to_string());
     }
@@ -51,6 +48,7 @@ pub struct ClosedIntervals {
 }
 
 impl ClosedIntervals {
+    #[allow(clippy::new_without_default)]
     pub fn new() -> ClosedIntervals {
         return ClosedIntervals { intervals: vec![] };
     }
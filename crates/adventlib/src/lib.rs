@@ -1,37 +1,154 @@
+pub mod answer;
+pub mod bigint;
+pub mod cancel;
+pub mod error;
 pub mod fraction;
 pub mod ilp;
 pub mod interval;
+pub mod log;
+pub mod params;
+pub mod stages;
+pub mod testing;
 pub mod util;
 pub mod vec;
 
-type Solver = fn(&str) -> String;
+use error::SolveError;
+use params::Params;
+use stages::Stages;
+
+pub use answer::Answer;
+
+pub type Solution = Result<Answer, SolveError>;
+
+pub type Solver = fn(&str) -> Solution;
+pub type ParamSolver = fn(&str, &Params) -> Solution;
+
+#[derive(Clone, Copy)]
+pub enum SolverFn {
+    Plain(Solver),
+    Params(ParamSolver),
+    Staged(&'static dyn Stages),
+}
 
 pub struct Part<'a> {
     pub name: &'a str,
     pub file: &'a str,
-    pub solver: Solver,
+    pub solver: SolverFn,
+    pub params: &'a [(&'a str, &'a str)], // Defaults for this part's input file
+    pub expected: Option<&'a str>,        // Known answer checked by the generated tests
 }
 
 impl<'a> Part<'a> {
     pub const fn new(name: &'a str, file: &'a str, solver: Solver) -> Self {
+        Self {
+            name: name,
+            file: file,
+            solver: SolverFn::Plain(solver),
+            params: &[],
+            expected: None,
+        }
+    }
+
+    // A part whose solver also takes parameters, such as puzzle constants which
+    // differ between the example and the real input
+    pub const fn with_params(
+        name: &'a str,
+        file: &'a str,
+        solver: ParamSolver,
+        params: &'a [(&'a str, &'a str)],
+    ) -> Self {
+        Self {
+            name: name,
+            file: file,
+            solver: SolverFn::Params(solver),
+            params: params,
+            expected: None,
+        }
+    }
+
+    // A part built from any kind of solver, as registered by #[solver]
+    pub const fn from_solver(name: &'a str, file: &'a str, solver: SolverFn) -> Self {
         Self {
             name: name,
             file: file,
             solver: solver,
+            params: &[],
+            expected: None,
+        }
+    }
+
+    pub const fn staged(name: &'a str, file: &'a str, stages: &'static dyn Stages) -> Self {
+        Self::from_solver(name, file, SolverFn::Staged(stages))
+    }
+
+    pub const fn params(mut self, params: &'a [(&'a str, &'a str)]) -> Self {
+        self.params = params;
+        self
+    }
+
+    pub fn default_params(&self) -> Params {
+        Params::new(self.params)
+    }
+
+    pub fn solve(&self, input: &str, params: &Params) -> Solution {
+        match self.solver {
+            SolverFn::Plain(solver) => solver(input),
+            SolverFn::Params(solver) => solver(input, params),
+            SolverFn::Staged(stages) => stages.solve(&stages.parse(input)?, params),
         }
     }
+
+    pub const fn expected(mut self, answer: &'a str) -> Self {
+        self.expected = Some(answer);
+        self
+    }
 }
 
+// Every puzzle of one year's event, with parts indexed by day - 1 and days
+// lacking a module left empty and out of `days`.
+pub struct Year<'a> {
+    pub year: u32,
+    pub days: &'a [usize],
+    pub parts: &'a [&'a [Part<'a>]],
+}
+
+impl<'a> Year<'a> {
+    pub fn max_day(&self) -> usize {
+        self.parts.len()
+    }
+
+    pub fn latest_day(&self) -> Option<usize> {
+        self.days.last().copied()
+    }
+
+    pub fn is_implemented(&self, day: usize) -> bool {
+        self.days.contains(&day)
+    }
+}
+
+// Each solver may be followed by `=> "answer"`, the expected answer for its
+// example which setup_problems! turns into a test. Starting the list with a
+// parse function and a semicolon makes every solver take its parsed value.
 #[macro_export]
 macro_rules! all_parts {
-    ( $( $solver:expr ),* ) => {
-        [ $( Part::new("Example", "example.txt", $solver), Part::new("Input", "input.txt", $solver) ),* ]
+    ( $parse:expr ; $( $solver:expr $( => $expected:literal )? ),* ) => {
+        [ $(
+            Part::staged("Example", "example.txt", &$crate::stages::Staged::new(stringify!($parse), $parse, |p, _| $solver(p)))
+                $( .expected($expected) )?,
+            Part::staged("Input", "input.txt", &$crate::stages::Staged::new(stringify!($parse), $parse, |p, _| $solver(p)))
+        ),* ]
+    };
+    ( $( $solver:expr $( => $expected:literal )? ),* ) => {
+        [ $(
+            Part::new("Example", "example.txt", $solver) $( .expected($expected) )?,
+            Part::new("Input", "input.txt", $solver)
+        ),* ]
     };
 }
 
 #[macro_export]
 macro_rules! example_parts {
-    ( $( $solver:expr ),* ) => {
-        [ $( Part::new("Example", "example.txt", $solver) ),* ]
+    ( $( $solver:expr $( => $expected:literal )? ),* ) => {
+        [ $( Part::new("Example", "example.txt", $solver) $( .expected($expected) )? ),* ]
     };
 }
@@ -0,0 +1,101 @@
+use std::{cell::RefCell, fmt};
+
+// How much a message matters, from warnings about odd input down to dumps of
+// intermediate state. The runner shows warnings and info with -v and
+// everything with -vv.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Warn,
+    Info,
+    Debug,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Level::Warn => write!(f, "warn"),
+            Level::Info => write!(f, "info"),
+            Level::Debug => write!(f, "debug"),
+        }
+    }
+}
+
+// A message logged by a solver along with the day and part it was running
+#[derive(Debug, Clone)]
+pub struct Record {
+    pub level: Level,
+    pub context: String,
+    pub message: String,
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}: {}", self.level, self.context, self.message)
+    }
+}
+
+struct Capture {
+    context: String,
+    max: Option<Level>,
+    records: Vec<Record>,
+}
+
+// Messages only go somewhere while the runner captures them on this thread,
+// so solvers stay silent in tests and by default
+thread_local! {
+    static CAPTURE: RefCell<Option<Capture>> = const { RefCell::new(None) };
+}
+
+// Whether a message at this level would be kept, for skipping expensive dumps
+pub fn enabled(level: Level) -> bool {
+    CAPTURE.with(|c| (c.borrow().as_ref()).is_some_and(|c| c.max.is_some_and(|m| level <= m)))
+}
+
+pub fn record(level: Level, message: fmt::Arguments) {
+    CAPTURE.with(|c| {
+        if let Some(c) = c.borrow_mut().as_mut()
+            && c.max.is_some_and(|m| level <= m)
+        {
+            let record = Record {
+                level: level,
+                context: c.context.clone(),
+                message: message.to_string(),
+            };
+            c.records.push(record);
+        }
+    })
+}
+
+// Puts back the capture which was active before, even if the function being
+// captured panics
+struct Restore(Option<Option<Capture>>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        if let Some(previous) = self.0.take() {
+            CAPTURE.with(|c| c.replace(previous));
+        }
+    }
+}
+
+// Runs the given function collecting the messages it logs up to the given
+// level, each labelled with the context such as the day and part
+pub fn capture<R>(context: &str, max: Option<Level>, f: impl FnOnce() -> R) -> (R, Vec<Record>) {
+    let capture = Capture {
+        context: context.to_string(),
+        max: max,
+        records: vec![],
+    };
+    let mut restore = Restore(Some(CAPTURE.with(|c| c.replace(Some(capture)))));
+    let result = f();
+    let capture = CAPTURE.with(|c| c.replace(restore.0.take().unwrap()));
+    (result, capture.map_or(vec![], |c| c.records))
+}
+
+// Logs a formatted message at the given level, e.g. `log!(Warn, "Bad {}", s)`
+#[macro_export]
+macro_rules! log {
+    ( $level:ident, $( $arg:tt )+ ) => {
+        $crate::log::record($crate::log::Level::$level, format_args!($( $arg )+))
+    };
+}
@@ -0,0 +1,56 @@
+use std::{fmt, str::FromStr};
+
+use crate::error::SolveError;
+
+// Named values a parameterized part is run with, starting from the defaults
+// declared for its input file. Values are kept as text and parsed into
+// whatever type the solver asks for.
+#[derive(Clone, Default)]
+pub struct Params {
+    values: Vec<(String, String)>,
+}
+
+impl Params {
+    pub fn new(defaults: &[(&str, &str)]) -> Self {
+        Params {
+            values: (defaults.iter())
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+
+    // Overrides a declared parameter, returning false if there is no such one
+    pub fn set(&mut self, name: &str, value: &str) -> bool {
+        match self.values.iter_mut().find(|(n, _)| n == name) {
+            Some((_, v)) => {
+                *v = value.to_string();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn get<T: FromStr>(&self, name: &str) -> Result<T, SolveError> {
+        let (_, value) = (self.values.iter())
+            .find(|(n, _)| n == name)
+            .ok_or_else(|| SolveError::unsupported(format!("Missing parameter {}", name)))?;
+        value.parse().map_err(|_| {
+            SolveError::unsupported(format!("Bad value for parameter {}: {}", name, value))
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values.iter().map(|(n, v)| (n.as_str(), v.as_str()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl fmt::Display for Params {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let values: Vec<_> = self.iter().map(|(n, v)| format!("{}={}", n, v)).collect();
+        write!(f, "{}", values.join(", "))
+    }
+}
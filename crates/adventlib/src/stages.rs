@@ -0,0 +1,57 @@
+use std::any::{Any, TypeId};
+
+use crate::{Solution, error::SolveError, params::Params};
+
+// The parsed input of a day with its type erased so the runner can share it
+// between every part solving the same file
+pub type Parsed = Box<dyn Any + Send + Sync>;
+
+// Identifies a parser by the type it produces and its name within the day.
+// Function pointers can't be used as they needn't be unique, and two parsers
+// merged into one address would share a parsed value of the wrong type.
+pub type ParserId = (TypeId, &'static str);
+
+// A part split into a parse step shared by the whole day and a solve step of
+// its own. Parts whose stages report the same parser get the same parsed value.
+pub trait Stages: Sync {
+    fn parse(&self, input: &str) -> Result<Parsed, SolveError>;
+    fn solve(&self, parsed: &Parsed, params: &Params) -> Solution;
+    fn parser(&self) -> ParserId;
+}
+
+pub struct Staged<T> {
+    name: &'static str, // Name of the parse function, unique within a day
+    parse: fn(&str) -> Result<T, SolveError>,
+    solve: fn(&T, &Params) -> Solution,
+}
+
+impl<T> Staged<T> {
+    pub const fn new(
+        name: &'static str,
+        parse: fn(&str) -> Result<T, SolveError>,
+        solve: fn(&T, &Params) -> Solution,
+    ) -> Self {
+        Staged {
+            name: name,
+            parse: parse,
+            solve: solve,
+        }
+    }
+}
+
+impl<T: Any + Send + Sync> Stages for Staged<T> {
+    fn parse(&self, input: &str) -> Result<Parsed, SolveError> {
+        Ok(Box::new((self.parse)(input)?))
+    }
+
+    fn solve(&self, parsed: &Parsed, params: &Params) -> Solution {
+        match parsed.downcast_ref::<T>() {
+            Some(parsed) => (self.solve)(parsed, params),
+            None => Err(SolveError::unsupported("Parsed input has the wrong type")),
+        }
+    }
+
+    fn parser(&self) -> ParserId {
+        (TypeId::of::<T>(), self.name)
+    }
+}
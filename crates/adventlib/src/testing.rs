@@ -0,0 +1,57 @@
+use std::{env, fs::read_to_string, path::PathBuf};
+
+use crate::Part;
+
+// Where the data of a day is found when testing: $ADVENT_DATA_DIR if set and
+// otherwise the given workspace data directory, as data/<year>/<day>/ or, for
+// the latest year, data/<day>/.
+fn day_dir(data_dir: &str, year: u32, day: usize, latest: bool) -> PathBuf {
+    let data_dir = env::var_os("ADVENT_DATA_DIR").map_or(PathBuf::from(data_dir), PathBuf::from);
+    let dir = data_dir.join(year.to_string()).join(day.to_string());
+    let legacy = data_dir.join(day.to_string());
+    if !dir.is_dir() && latest && legacy.is_dir() {
+        return legacy;
+    }
+    dir
+}
+
+// Runs every part with an expected answer against its file and panics listing
+// each mismatch. Only examples are checked in, and a missing one is a failure
+// too so that a test never passes without solving anything.
+pub fn check_examples(data_dir: &str, year: u32, day: usize, latest: bool, parts: &[Part]) {
+    let dir = day_dir(data_dir, year, day, latest);
+    let mut failures = vec![];
+    for part in parts {
+        let Some(expected) = part.expected else {
+            continue;
+        };
+        let path = dir.join(part.file);
+        let Ok(content) = read_to_string(&path) else {
+            failures.push(format!(
+                "{} ({}): {} not found",
+                part.name,
+                part.file,
+                path.display()
+            ));
+            continue;
+        };
+        let input = content.trim();
+        match part.solve(input, &part.default_params()) {
+            Ok(answer) if answer.matches(expected) => {}
+            Ok(answer) => failures.push(format!(
+                "{} ({}): expected {}, got {}",
+                part.name, part.file, expected, answer
+            )),
+            Err(err) => failures.push(format!(
+                "{} ({}): expected {}, got error {}",
+                part.name,
+                part.file,
+                expected,
+                err.locate(input)
+            )),
+        }
+    }
+    if !failures.is_empty() {
+        panic!("{} {}:\n{}", year, day, failures.join("\n"));
+    }
+}
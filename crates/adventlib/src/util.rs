@@ -1,10 +1,11 @@
 use std::{
     any::type_name,
-    fmt,
     ops::{Add, Rem},
     str::FromStr,
 };
 
+use crate::error::SolveError;
+
 pub trait Partition<T> {
     fn partition<F>(&mut self, indicator: F) -> usize
     where
@@ -16,6 +17,9 @@ impl<T> Partition<T> for Vec<T> {
     where
         F: Fn(&T) -> bool,
     {
+        if self.is_empty() {
+            return 0;
+        }
         let mut j = self.len() - 1;
         for i in 0..self.len() {
             if !indicator(&self[i]) {
@@ -41,15 +45,13 @@ pub fn posmod<T: Copy + Add<Output = T> + Rem<Output = T>>(i: T, m: T) -> T {
     return ((i % m) + m) % m;
 }
 
-pub fn parse<T: FromStr>(s: &str) -> T
-where
-    <T as FromStr>::Err: fmt::Debug,
-{
+pub fn parse<T: FromStr>(s: &str) -> Result<T, SolveError> {
     s.parse::<T>()
-        .expect(&format!("Invalid {}: [{}]", type_name::<T>(), s))
+        .map_err(|_| SolveError::parse(s, format!("Invalid {}: [{}]", type_name::<T>(), s)))
 }
 
 #[allow(dead_code)]
+#[allow(clippy::ptr_arg)]
 pub fn zip<'a, A, B>(a: &'a Vec<A>, b: &'a Vec<B>) -> impl Iterator<Item = (&'a A, &'a B)> {
     a.iter().zip(b.iter())
 }
@@ -59,16 +61,7 @@ pub fn zop<'a, T>(a: &'a Vec<T>, b: &'a Vec<T>, f: fn(&'a T, &'a T) -> T) -> Vec
     zip(a, b).map(|(aa, bb)| f(aa, bb)).collect()
 }
 
-pub fn mid(s: &str, i: usize) -> &str {
-    &s[i..(s.len() - i)]
-}
-
-pub const fn gcd(mut a: i32, mut b: i32) -> i32 {
-    if b > a {
-        (a, b) = (b, a);
-    }
-    while b != 0 {
-        (a, b) = (b, a % b);
-    }
-    a
+// Strips i bytes from both ends, or None if the string is too short
+pub fn mid(s: &str, i: usize) -> Option<&str> {
+    s.get(i..s.len().checked_sub(i)?)
 }
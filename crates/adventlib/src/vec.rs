@@ -1,11 +1,19 @@
+use crate::{error::SolveError, util::parse};
+
 #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, PartialOrd, Ord)]
 pub struct Vec3(pub i64, pub i64, pub i64);
 
 impl Vec3 {
-    pub fn parse(s: &str) -> Self {
-        let mut vals: Vec<_> = s.split(',').filter_map(|i| i.parse().ok()).collect();
-        vals.resize(3, 0);
-        return Vec3(vals[0], vals[1], vals[2]);
+    // Reads "x,y,z" or, for points in the plane, "x,y" with z left at 0
+    pub fn parse(s: &str) -> Result<Self, SolveError> {
+        let vals = (s.split(','))
+            .map(|i| parse::<i64>(i.trim()))
+            .collect::<Result<Vec<_>, _>>()?;
+        match vals[..] {
+            [x, y] => Ok(Vec3(x, y, 0)),
+            [x, y, z] => Ok(Vec3(x, y, z)),
+            _ => Err(SolveError::parse(s, "Expected 'x,y' or 'x,y,z'")),
+        }
     }
 
     pub const fn sq_dist(&self, other: &Vec3) -> i64 {
@@ -40,6 +48,7 @@ impl Vec3 {
         Vec3(self.0.signum(), self.1.signum(), self.2.signum())
     }
 
+    #[allow(clippy::len_without_is_empty)]
     pub const fn len(self) -> i64 {
         self.0 + self.1 + self.2
     }
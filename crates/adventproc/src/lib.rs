@@ -1,14 +1,484 @@
 extern crate proc_macro;
-use proc_macro::TokenStream;
+use std::{
+    fs::{read_dir, read_to_string},
+    path::PathBuf,
+};
 
-#[proc_macro]
-pub fn setup_problems(max: TokenStream) -> TokenStream {
-    let max_day: usize = format!("{max}").parse().unwrap();
-    let mods: String = (1..=max_day).map(|i| format!("mod day{i};\n")).collect();
-    let parts: String = (1..=max_day)
-        .map(|i| format!("&day{i}::PARTS,\n"))
+use proc_macro::{Delimiter, TokenStream, TokenTree};
+
+// Arguments of a #[solver(day = N, part = N, input = "..", example = "..")]
+// attribute. Either file may be left out to skip running against it and an
+// optional year = N is checked against the directory the module lives in. The
+// answer to the example may be given as expected = ".." to test it. Solvers
+// taking &Params declare the defaults for each file as params = "name=value,.."
+// and example_params = "..".
+struct SolverArgs {
+    year: Option<u32>,
+    day: usize,
+    part: usize,
+    input: Option<String>,
+    example: Option<String>,
+    expected: Option<String>,
+    params: Option<Vec<(String, String)>>,
+    example_params: Option<Vec<(String, String)>>,
+}
+
+fn compile_error(message: &str) -> TokenStream {
+    format!("compile_error!({:?});", message).parse().unwrap()
+}
+
+fn parse_solver_args(args: TokenStream) -> Result<SolverArgs, String> {
+    let (mut year, mut day, mut part) = (None, None, None);
+    let (mut input, mut example, mut expected) = (None, None, None);
+    let (mut params, mut example_params) = (None, None);
+    let tokens: Vec<_> = args.into_iter().collect();
+    for arg in tokens.split(|t| matches!(t, TokenTree::Punct(p) if p.as_char() == ',')) {
+        let (key, value) = match arg {
+            [] => continue,
+            [
+                TokenTree::Ident(key),
+                TokenTree::Punct(eq),
+                TokenTree::Literal(value),
+            ] if eq.as_char() == '=' => (key.to_string(), value.to_string()),
+            _ => return Err("solver arguments must look like `key = value`".to_string()),
+        };
+        let number =
+            || (value.parse::<usize>()).map_err(|_| format!("solver `{key}` must be a number"));
+        let year_number =
+            || (value.parse::<u32>()).map_err(|_| format!("solver `{key}` must be a year"));
+        let string = || match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+            Some(s) => Ok(s.to_string()),
+            None => Err(format!("solver `{key}` must be a string")),
+        };
+        let pairs = || {
+            (string()?.split(','))
+                .map(|pair| match pair.split_once('=') {
+                    Some((name, value)) if !name.trim().is_empty() => {
+                        Ok((name.trim().to_string(), value.trim().to_string()))
+                    }
+                    _ => Err(format!("solver `{key}` must look like \"name=value,..\"")),
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        match key.as_str() {
+            "year" => year = Some(year_number()?),
+            "day" => day = Some(number()?),
+            "part" => part = Some(number()?),
+            "input" => input = Some(string()?),
+            "example" => example = Some(string()?),
+            // Numeric answers needn't be quoted
+            "expected" => expected = Some(string().or(number().map(|n| n.to_string()))?),
+            "params" => params = Some(pairs()?),
+            "example_params" => example_params = Some(pairs()?),
+            _ => return Err(format!("unknown solver argument `{key}`")),
+        }
+    }
+    let day = day.ok_or("solver is missing `day = N`")?;
+    let part = part.ok_or("solver is missing `part = N`")?;
+    if input.is_none() && example.is_none() {
+        return Err("solver needs at least one of `input` or `example`".to_string());
+    }
+    if expected.is_some() && example.is_none() {
+        return Err("solver `expected` needs an `example` to check".to_string());
+    }
+    if params.is_some() && input.is_none() {
+        return Err("solver `params` needs an `input` to run with".to_string());
+    }
+    if example_params.is_some() && example.is_none() {
+        return Err("solver `example_params` needs an `example` to run with".to_string());
+    }
+    Ok(SolverArgs {
+        year: year,
+        day: day,
+        part: part,
+        input: input,
+        example: example,
+        expected: expected,
+        params: params,
+        example_params: example_params,
+    })
+}
+
+// The .params(..) call declaring the defaults of a part, if it has any
+fn params_call(params: &Option<Vec<(String, String)>>) -> String {
+    match params {
+        Some(params) => {
+            let pairs: Vec<_> = (params.iter())
+                .map(|(name, value)| format!("({name:?}, {value:?})"))
+                .collect();
+            format!(".params(&[{}])", pairs.join(", "))
+        }
+        None => String::new(),
+    }
+}
+
+// The name, parameter types and return type of a function item
+struct Signature {
+    name: String,
+    params: Vec<String>,
+    output: String,
+}
+
+fn is_punct(token: &TokenTree, c: char) -> bool {
+    matches!(token, TokenTree::Punct(p) if p.as_char() == c)
+}
+
+// Splits tokens on top level commas, which includes those between angle
+// brackets as generics aren't grouped like other brackets
+fn split_commas(tokens: &[TokenTree]) -> Vec<&[TokenTree]> {
+    let (mut parts, mut depth, mut start) = (vec![], 0, 0);
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            _ if is_punct(token, '<') => depth += 1,
+            // Skip the arrow of function types
+            _ if is_punct(token, '>') && !(i > 0 && is_punct(&tokens[i - 1], '-')) => depth -= 1,
+            _ if is_punct(token, ',') && depth == 0 => {
+                parts.push(&tokens[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&tokens[start..]);
+    parts.retain(|p| !p.is_empty());
+    parts
+}
+
+fn signature(item: &TokenStream) -> Option<Signature> {
+    let tokens: Vec<_> = item.clone().into_iter().collect();
+    let start = tokens
+        .iter()
+        .position(|t| matches!(t, TokenTree::Ident(i) if i.to_string() == "fn"))?;
+    let name = tokens.get(start + 1)?.to_string();
+    let (offset, TokenTree::Group(params)) = tokens[start..].iter().enumerate().find(
+        |(_, t)| matches!(t, TokenTree::Group(g) if g.delimiter() == Delimiter::Parenthesis),
+    )?
+    else {
+        return None;
+    };
+    let params: Vec<_> = params.stream().into_iter().collect();
+    let params = split_commas(&params)
+        .iter()
+        .map(|param| {
+            let colon = param.iter().position(|t| is_punct(t, ':')).unwrap_or(0);
+            let ty: TokenStream = param[colon + 1..].iter().cloned().collect();
+            ty.to_string().replace(' ', "")
+        })
         .collect();
-    format!("{mods}\nstatic PROBLEMS: &'static [&'static [Part<'static>]] = &[\n{parts}];")
-        .parse()
-        .unwrap()
+    // Everything between the parameters and the body is the return type
+    let rest = &tokens[start + offset + 1..];
+    let end = rest
+        .iter()
+        .position(|t| matches!(t, TokenTree::Group(g) if g.delimiter() == Delimiter::Brace))
+        .unwrap_or(rest.len());
+    let output = match &rest[..end] {
+        [arrow, gt, output @ ..] if is_punct(arrow, '-') && is_punct(gt, '>') => {
+            output.iter().cloned().collect::<TokenStream>().to_string()
+        }
+        _ => String::new(),
+    };
+    Some(Signature {
+        name: name,
+        params: params,
+        output: output,
+    })
+}
+
+fn registration_name(part: usize) -> String {
+    format!("__SOLVER_PART_{part}")
+}
+
+// Marks a function as the solver for one part of a day. The function is left
+// as is and a crate visible constant is emitted next to it, both to check its
+// signature and so setup_problems! can refer to it from the crate root.
+// Solvers take the input as &str, or the value returned by the day's #[parser]
+// function, optionally followed by the part's &Params whose defaults come from
+// the params and example_params arguments.
+#[proc_macro_attribute]
+pub fn solver(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = match parse_solver_args(args) {
+        Ok(args) => args,
+        Err(err) => return [compile_error(&err), item].into_iter().collect(),
+    };
+    let Some(signature) = signature(&item) else {
+        return compile_error("#[solver] can only be applied to functions");
+    };
+    let name = &signature.name;
+    let staged = signature.params.first().is_some_and(|p| p != "&str");
+    let solver = match (staged, signature.params.len()) {
+        (false, 1) => format!("adventlib::SolverFn::Plain({name})"),
+        (false, 2) => format!("adventlib::SolverFn::Params({name})"),
+        (true, 1) => format!(
+            "adventlib::SolverFn::Staged(&adventlib::stages::Staged::new(__PARSER_NAME, __PARSER, |p, _| {name}(p)))"
+        ),
+        (true, 2) => format!(
+            "adventlib::SolverFn::Staged(&adventlib::stages::Staged::new(__PARSER_NAME, __PARSER, {name}))"
+        ),
+        _ => {
+            let err = compile_error("solvers take the input and optionally &Params");
+            return [err, item].into_iter().collect();
+        }
+    };
+    // Params::get only finds declared parameters, so every file a solver taking
+    // &Params runs against needs its defaults
+    let takes_params = signature.params.len() == 2;
+    let missing = match (takes_params, &args.input, &args.example) {
+        (true, Some(_), _) if args.params.is_none() => Some("params"),
+        (true, _, Some(_)) if args.example_params.is_none() => Some("example_params"),
+        (false, _, _) if args.params.is_some() || args.example_params.is_some() => {
+            let err = compile_error("solver parameters need a solver taking &Params");
+            return [err, item].into_iter().collect();
+        }
+        _ => None,
+    };
+    if let Some(key) = missing {
+        let err = compile_error(&format!(
+            "solvers taking &Params need their defaults as `{key} = \"name=value,..\"`"
+        ));
+        return [err, item].into_iter().collect();
+    }
+    let registration: TokenStream = format!(
+        "#[doc(hidden)]\npub(crate) const {}: adventlib::SolverFn = {};",
+        registration_name(args.part),
+        solver
+    )
+    .parse()
+    .unwrap();
+    [item, registration].into_iter().collect()
+}
+
+// Marks the function parsing the input of a day into the value its solvers
+// take. Only one parser is allowed per day module.
+#[proc_macro_attribute]
+pub fn parser(args: TokenStream, item: TokenStream) -> TokenStream {
+    if !args.is_empty() {
+        return [compile_error("#[parser] takes no arguments"), item]
+            .into_iter()
+            .collect();
+    }
+    let Some(signature) = signature(&item) else {
+        return compile_error("#[parser] can only be applied to functions");
+    };
+    // The name tells parsers apart in the runner's cache, as function pointers
+    // needn't be unique
+    let registration: TokenStream = format!(
+        "#[doc(hidden)]\nconst __PARSER: fn(&str) -> {} = {};\n\
+         #[doc(hidden)]\nconst __PARSER_NAME: &str = {:?};",
+        signature.output, signature.name, signature.name
+    )
+    .parse()
+    .unwrap();
+    [item, registration].into_iter().collect()
+}
+
+// Finds every #[solver(..)] attribute at the top level of a source file
+fn find_solvers(source: TokenStream) -> Result<Vec<SolverArgs>, String> {
+    let tokens: Vec<_> = source.into_iter().collect();
+    let mut solvers = vec![];
+    for pair in tokens.windows(2) {
+        let (TokenTree::Punct(hash), TokenTree::Group(attr)) = (&pair[0], &pair[1]) else {
+            continue;
+        };
+        if hash.as_char() != '#' || attr.delimiter() != Delimiter::Bracket {
+            continue;
+        }
+        // Accept both #[solver(..)] and paths like #[adventproc::solver(..)]
+        let inner: Vec<_> = attr.stream().into_iter().collect();
+        if let [.., TokenTree::Ident(name), TokenTree::Group(args)] = inner.as_slice()
+            && name.to_string() == "solver"
+            && args.delimiter() == Delimiter::Parenthesis
+        {
+            solvers.push(parse_solver_args(args.stream())?);
+        }
+    }
+    solvers.sort_by_key(|s| s.part);
+    Ok(solvers)
+}
+
+// Builds the parts of a day, either from its #[solver] functions or, for days
+// which don't use the attribute, from a hand written PARTS static.
+fn day_parts(year: u32, day: usize, module: &str, path: &PathBuf) -> Result<String, String> {
+    let source = read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    let Ok(tokens) = source.parse::<TokenStream>() else {
+        // The module itself will report a better error
+        return Ok(format!("&{module}::PARTS"));
+    };
+    let name = path.display();
+    let solvers = find_solvers(tokens).map_err(|err| format!("{name}: {err}"))?;
+    if solvers.is_empty() {
+        return Ok(format!("&{module}::PARTS"));
+    }
+    let mut parts = vec![];
+    for solver in solvers {
+        if solver.day != day || solver.year.is_some_and(|y| y != year) {
+            return Err(format!(
+                "{name}: solver for part {} is marked as {}day {}",
+                solver.part,
+                solver.year.map_or(String::new(), |y| format!("{y} ")),
+                solver.day
+            ));
+        }
+        let registration = format!("{module}::{}", registration_name(solver.part));
+        if let Some(example) = solver.example {
+            let expected = match solver.expected {
+                Some(answer) => format!(".expected({answer:?})"),
+                None => String::new(),
+            };
+            parts.push(format!(
+                "Part::from_solver(\"Example\", {example:?}, {registration}){}{expected}",
+                params_call(&solver.example_params)
+            ));
+        }
+        if let Some(input) = solver.input {
+            parts.push(format!(
+                "Part::from_solver(\"Input\", {input:?}, {registration}){}",
+                params_call(&solver.params)
+            ));
+        }
+    }
+    Ok(format!("&[{}]", parts.join(", ")))
+}
+
+// Finds the days with a dayN.rs module in the given directory
+fn discover_days(src: &PathBuf) -> Result<Vec<usize>, String> {
+    let entries = read_dir(src).map_err(|err| format!("{}: {}", src.display(), err))?;
+    let mut days = vec![];
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(number) = name.strip_prefix("day").and_then(|n| n.strip_suffix(".rs")) else {
+            continue;
+        };
+        // Other modules such as days.rs or day_util.rs are left alone
+        if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+        match number.parse::<usize>() {
+            Ok(day) if day > 0 && day.to_string() == number => days.push(day),
+            Ok(day) if day > 0 => {
+                return Err(format!(
+                    "{name} should be named day{day}.rs to be picked up"
+                ));
+            }
+            _ => {}
+        }
+    }
+    days.sort();
+    Ok(days)
+}
+
+// The year of the calling crate's own src/dayN.rs modules, taken from the
+// digits its package name ends with (e.g. advent2025)
+fn crate_year() -> Option<u32> {
+    let name = std::env::var("CARGO_PKG_NAME").ok()?;
+    let digits = name.trim_start_matches(|c: char| !c.is_ascii_digit());
+    digits.parse().ok()
+}
+
+// Finds every year with day modules: the crate's own year in src/ itself and
+// any other years in src/yearYYYY/ directories.
+fn discover_years(src: &PathBuf) -> Result<Vec<(u32, PathBuf, Vec<usize>)>, String> {
+    let mut years = vec![];
+    let own_days = discover_days(src)?;
+    if !own_days.is_empty() {
+        let year = crate_year().ok_or(
+            "can't tell which year src/dayN.rs belong to, name the package adventYYYY \
+             or move them into src/yearYYYY/",
+        )?;
+        years.push((year, src.clone(), own_days));
+    }
+    let entries = read_dir(src).map_err(|err| format!("{}: {}", src.display(), err))?;
+    for entry in entries.flatten().filter(|e| e.path().is_dir()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(year) = name
+            .strip_prefix("year")
+            .and_then(|y| y.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        if years.iter().any(|(y, _, _)| *y == year) {
+            return Err(format!(
+                "days for {year} are in both src/ and src/{name}/, keep them in one place"
+            ));
+        }
+        let days = discover_days(&entry.path())?;
+        if !days.is_empty() {
+            years.push((year, entry.path(), days));
+        }
+    }
+    years.sort_by_key(|(year, _, _)| *year);
+    if years.is_empty() {
+        return Err(format!(
+            "no dayN.rs modules found in {} or its yearYYYY directories",
+            src.display()
+        ));
+    }
+    Ok(years)
+}
+
+// Declares a module for each dayN.rs in the calling crate's src directory (and
+// src/yearYYYY/ for other years) and collects their parts into PROBLEMS, one
+// Year per event sorted by year. A test per day checks the parts which have an
+// expected answer against the workspace's data directory.
+#[proc_macro]
+pub fn setup_problems(args: TokenStream) -> TokenStream {
+    if !args.is_empty() {
+        return compile_error(&format!(
+            "setup_problems! takes no arguments (got `{args}`), days are discovered from src/dayN.rs"
+        ));
+    }
+    let Ok(manifest_dir) = std::env::var("CARGO_MANIFEST_DIR") else {
+        return compile_error("setup_problems! needs CARGO_MANIFEST_DIR to find day modules");
+    };
+    let src = PathBuf::from(manifest_dir).join("src");
+    let years = match discover_years(&src) {
+        Ok(years) => years,
+        Err(err) => return compile_error(&err),
+    };
+    let mut mods = String::new();
+    let mut calendar = String::new();
+    let mut tests = String::new();
+    let latest = years.last().unwrap().0;
+    for (i, (year, dir, days)) in years.into_iter().enumerate() {
+        let mut parts = String::new();
+        for day in 1..=*days.last().unwrap() {
+            if !days.contains(&day) {
+                parts.push_str("&[], ");
+                continue;
+            }
+            // The crate's own year keeps plain dayN module names
+            let module = if dir == src {
+                mods.push_str(&format!("mod day{day};\n"));
+                format!("day{day}")
+            } else {
+                let relative = dir.strip_prefix(&src).unwrap().join(format!("day{day}.rs"));
+                mods.push_str(&format!(
+                    "#[path = {:?}]\nmod year{year}_day{day};\n",
+                    relative.to_string_lossy()
+                ));
+                format!("year{year}_day{day}")
+            };
+            tests.push_str(&format!(
+                "#[test]\nfn year{year}_day{day}() {{\n    adventlib::testing::check_examples(\n        \
+                 concat!(env!(\"CARGO_MANIFEST_DIR\"), \"/../../data\"), {year}, {day}, {},\n        \
+                 super::PROBLEMS[{i}].parts[{}],\n    );\n}}\n",
+                year == latest,
+                day - 1
+            ));
+            match day_parts(year, day, &module, &dir.join(format!("day{day}.rs"))) {
+                Ok(day_parts) => parts.push_str(&format!("{day_parts},\n")),
+                Err(err) => return compile_error(&err),
+            }
+        }
+        let days: Vec<_> = days.iter().map(|d| d.to_string()).collect();
+        calendar.push_str(&format!(
+            "Year {{ year: {year}, days: &[{}], parts: &[\n{parts}] }},\n",
+            days.join(", ")
+        ));
+    }
+    format!(
+        "{mods}\nstatic PROBLEMS: &'static [Year<'static>] = &[\n{calendar}];\n\
+         #[cfg(test)]\nmod example_tests {{\n{tests}}}"
+    )
+    .parse()
+    .unwrap()
 }